@@ -1,38 +1,223 @@
 use petgraph::graph::DiGraph;
 use petgraph::prelude::*;
+use petgraph::dot::{Config, Dot};
+use petgraph::unionfind::UnionFind;
+use petgraph::algo::{dijkstra, toposort};
+use petgraph::visit::{IntoEdges, Reversed, Visitable};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::error::Error;
-use std::fs::OpenOptions;
-use std::io::BufWriter;
-use simple_pagerank::Pagerank;
-use rustworkx_core::centrality::{betweenness_centrality, closeness_centrality};
+use std::borrow::Cow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufWriter, Write};
+use rustworkx_core::centrality::{betweenness_centrality, closeness_centrality, eigenvector_centrality};
 use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use csv::Writer;
+use crate::error::AnalysisError;
+use crate::progress::Progress;
 
 
+// Field names double as the NDJSON keys, and the aliases let records use the
+// CSV column names instead. Missing fields take their defaults.
 #[derive(Debug, Deserialize, Default)]
+#[serde(default)]
 pub struct Game {
+    #[serde(alias = "GameID")]
     pub game_id: String,
+    #[serde(alias = "Event")]
     pub event: String,
+    #[serde(alias = "White")]
     pub white: String,
+    #[serde(alias = "WhiteElo")]
     pub white_elo: Option<u32>,
+    #[serde(alias = "WhiteRatingDiff")]
     pub white_rating_diff: Option<f32>,
+    #[serde(alias = "White_tosViolation")]
     pub white_tos_violation: Option<bool>,
+    #[serde(alias = "White_playTime_total")]
     pub white_play_time_total: Option<String>,
+    #[serde(alias = "White_count_all")]
     pub white_count_all: Option<u32>,
+    #[serde(alias = "Black")]
     pub black: String,
+    #[serde(alias = "BlackElo")]
     pub black_elo: Option<u32>,
+    #[serde(alias = "BlackRatingDiff")]
     pub black_rating_diff: Option<f32>,
+    #[serde(alias = "Black_tosViolation")]
     pub black_tos_violation: Option<bool>,
+    #[serde(alias = "Black_playTime_total")]
     pub black_play_time_total: Option<String>,
+    #[serde(alias = "Black_count_all")]
     pub black_count_all: Option<u32>,
+    #[serde(alias = "Moves")]
     pub moves: String,
+    #[serde(alias = "TotalMoves")]
     pub total_moves: Option<u32>,
+    #[serde(alias = "ECO")]
     pub eco: String,
+    #[serde(alias = "Opening")]
     pub opening: String,
+    #[serde(alias = "TimeControl")]
     pub time_control: String,
+    #[serde(alias = "Result")]
     pub result: String,
+    #[serde(alias = "Date", alias = "UTCDate")]
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWin,
+    BlackWin,
+    Draw,
+}
+
+impl GameResult {
+    /// Accepts PGN results ("1-0", "0-1", "1/2-1/2") as well as the Lichess
+    /// termination encoding ("Normal", "Time forfeit"), where the winner is
+    /// whoever gained rating.
+    pub fn from_game(game: &Game) -> Option<GameResult> {
+        GameResult::from_parts(&game.result, game.white_rating_diff, game.black_rating_diff)
+    }
+
+    pub fn from_parts(result: &str, white_rating_diff: Option<f32>, black_rating_diff: Option<f32>) -> Option<GameResult> {
+        GameResult::from_parts_with(ResultScheme::Pgn, result, white_rating_diff, black_rating_diff)
+            .or_else(|| GameResult::from_parts_with(ResultScheme::Termination, result, white_rating_diff, black_rating_diff))
+    }
+
+    /// Reads `result` only as `scheme` encodes it.
+    pub fn from_parts_with(scheme: ResultScheme, result: &str, white_rating_diff: Option<f32>, black_rating_diff: Option<f32>) -> Option<GameResult> {
+        let white_gained = white_rating_diff.unwrap_or(0.0) > 0.0;
+        let black_gained = black_rating_diff.unwrap_or(0.0) > 0.0;
+
+        match scheme {
+            ResultScheme::Pgn => match result {
+                "1-0" => Some(GameResult::WhiteWin),
+                "0-1" => Some(GameResult::BlackWin),
+                "1/2-1/2" => Some(GameResult::Draw),
+                _ => None,
+            },
+            ResultScheme::Termination => match result {
+                "Normal" => {
+                    if white_gained {
+                        Some(GameResult::WhiteWin)
+                    } else if black_gained {
+                        Some(GameResult::BlackWin)
+                    } else {
+                        Some(GameResult::Draw)
+                    }
+                }
+                "Time forfeit" => {
+                    if white_gained {
+                        Some(GameResult::WhiteWin)
+                    } else {
+                        Some(GameResult::BlackWin)
+                    }
+                }
+                _ => None,
+            },
+            ResultScheme::Winner => match result.trim().to_ascii_lowercase().as_str() {
+                "white" => Some(GameResult::WhiteWin),
+                "black" => Some(GameResult::BlackWin),
+                "draw" => Some(GameResult::Draw),
+                _ => None,
+            },
+            ResultScheme::Numeric => {
+                let white_score: f64 = result.trim().parse().ok()?;
+                if white_score == 1.0 {
+                    Some(GameResult::WhiteWin)
+                } else if white_score == 0.0 {
+                    Some(GameResult::BlackWin)
+                } else if white_score == 0.5 {
+                    Some(GameResult::Draw)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn to_pgn(self) -> &'static str {
+        match self {
+            GameResult::WhiteWin => "1-0",
+            GameResult::BlackWin => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+}
+
+/// How an export writes the Result column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultScheme {
+    /// "1-0", "0-1", "1/2-1/2".
+    Pgn,
+    /// Lichess terminations ("Normal", "Time forfeit"), with the winner taken
+    /// from the rating diffs.
+    Termination,
+    /// The winning side, "White" or "Black", or "Draw".
+    Winner,
+    /// White's score: 1, 0 or 0.5.
+    Numeric,
+}
+
+impl ResultScheme {
+    const ALL: [ResultScheme; 4] = [ResultScheme::Pgn, ResultScheme::Termination, ResultScheme::Winner, ResultScheme::Numeric];
+}
+
+const RESULT_SCHEME_SAMPLE: usize = 1000;
+
+// Looks at the first non-empty results and picks the scheme that can read
+// the most of them, preferring earlier schemes in `ResultScheme::ALL` on a
+// tie. With nothing to go on it's PGN.
+pub fn detect_result_scheme(games: &[Game]) -> ResultScheme {
+    detect_result_scheme_from_parts(games.iter().map(|game| (game.result.as_str(), game.white_rating_diff, game.black_rating_diff)))
+}
+
+// `detect_result_scheme` over (result, white rating diff, black rating diff)
+// rows, for callers that don't have `Game`s.
+pub fn detect_result_scheme_from_parts<'a>(rows: impl Iterator<Item = (&'a str, Option<f32>, Option<f32>)>) -> ResultScheme {
+    let sample: Vec<(&str, Option<f32>, Option<f32>)> = rows.filter(|(result, _, _)| !result.trim().is_empty()).take(RESULT_SCHEME_SAMPLE).collect();
+    let mut best = (ResultScheme::Pgn, 0);
+    for scheme in ResultScheme::ALL {
+        let readable = sample
+            .iter()
+            .filter(|&&(result, white_rating_diff, black_rating_diff)| GameResult::from_parts_with(scheme, result, white_rating_diff, black_rating_diff).is_some())
+            .count();
+        if readable > best.1 {
+            best = (scheme, readable);
+        }
+    }
+    best.0
+}
+
+// Rewrites every result `scheme` can read as its PGN form, which is what the
+// rest of the analysis expects. Results it can't read are left alone.
+pub fn normalize_results(games: &mut [Game], scheme: ResultScheme) {
+    if scheme == ResultScheme::Pgn {
+        return;
+    }
+    for game in games {
+        if let Some(result) = GameResult::from_parts_with(scheme, &game.result, game.white_rating_diff, game.black_rating_diff) {
+            game.result = result.to_pgn().to_string();
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PlayerScore<'a> {
+    player: &'a str,
+    score: f64,
+}
+
+#[derive(Serialize)]
+struct PlayerPerformanceRecord<'a> {
+    player: &'a str,
+    #[serde(flatten)]
+    performance: &'a PlayerPerformance,
 }
 
 #[derive(Default, Debug, Serialize)]
@@ -58,6 +243,15 @@ impl PlayerPerformance {
         self.win_rate = self.calculate_win_rate();
     }
 
+    pub fn merge(&mut self, other: &PlayerPerformance) {
+        self.games_played += other.games_played;
+        self.games_won += other.games_won;
+        self.games_lost += other.games_lost;
+        self.games_drawn += other.games_drawn;
+        self.total_rating_change += other.total_rating_change;
+        self.win_rate = self.calculate_win_rate();
+    }
+
     fn calculate_win_rate(&self) -> f64 {
         if self.games_played == 0 {
             0.0
@@ -67,53 +261,107 @@ impl PlayerPerformance {
     }
 }
 
-pub fn read_games_from_dataframe(df: &DataFrame) -> Result<Vec<Game>, Box<dyn Error>> {
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlayerRatingStats {
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub count: u32,
+}
+
+impl PlayerRatingStats {
+    pub fn from_values(values: &[f64]) -> PlayerRatingStats {
+        let count = values.len();
+        if count == 0 {
+            return PlayerRatingStats { mean: 0.0, median: 0.0, std_dev: 0.0, count: 0 };
+        }
+
+        let mean = values.iter().sum::<f64>() / count as f64;
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = if count.is_multiple_of(2) {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+        } else {
+            sorted[count / 2]
+        };
+
+        let std_dev = if count == 1 {
+            0.0
+        } else {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64).sqrt()
+        };
+
+        PlayerRatingStats { mean, median, std_dev, count: count as u32 }
+    }
+}
+
+fn column<'a>(df: &'a DataFrame, name: &str) -> Result<&'a Series, AnalysisError> {
+    df.column(name).map_err(|_| AnalysisError::MissingColumn(name.to_string()))
+}
+
+fn optional_column<'a>(df: &'a DataFrame, name: &str) -> Option<&'a Series> {
+    df.column(name).ok()
+}
+
+/// Columns `read_games_from_dataframe` can't do without; the rest are optional.
+pub const REQUIRED_COLUMNS: [&str; 3] = ["White", "Black", "Result"];
+
+pub fn read_games_from_dataframe(df: &DataFrame) -> Result<Vec<Game>, AnalysisError> {
     let mut games = Vec::new();
     let row_count = df.height();
 
-    let game_id_col = df.column("GameID")?.utf8()?;
-    let event_col = df.column("Event")?.utf8()?;
-    let white_col = df.column("White")?.utf8()?;
-    let white_elo_col = df.column("WhiteElo")?.i64()?;
-    let white_rating_diff_col = df.column("WhiteRatingDiff")?.f64()?;
-    let white_tos_violation_col = df.column("White_tosViolation")?.bool()?;
-    let white_play_time_total_col = df.column("White_playTime_total")?.f64()?;
-    let white_count_all_col = df.column("White_count_all")?.f64()?;
-    let black_col = df.column("Black")?.utf8()?;
-    let black_elo_col = df.column("BlackElo")?.i64()?;
-    let black_rating_diff_col = df.column("BlackRatingDiff")?.f64()?;
-    let black_tos_violation_col = df.column("Black_tosViolation")?.bool()?;
-    let black_play_time_total_col = df.column("Black_playTime_total")?.f64()?;
-    let black_count_all_col = df.column("Black_count_all")?.f64()?; 
-    let moves_col = df.column("Moves")?.utf8()?;
-    let total_moves_col = df.column("TotalMoves")?.i64()?;
-    let eco_col = df.column("ECO")?.utf8()?;
-    let opening_col = df.column("Opening")?.utf8()?;
-    let time_control_col = df.column("TimeControl")?.utf8()?;
-    let result_col = df.column("Result")?.utf8()?;
+    let white_col = column(df, "White")?.utf8()?;
+    let black_col = column(df, "Black")?.utf8()?;
+    // A Result column of plain scores is read in as numbers.
+    let result_series = column(df, "Result")?.cast(&DataType::Utf8)?;
+    let result_col = result_series.utf8()?;
+
+    let game_id_col = optional_column(df, "GameID").map(|s| s.utf8()).transpose()?;
+    let event_col = optional_column(df, "Event").map(|s| s.utf8()).transpose()?;
+    let white_elo_col = optional_column(df, "WhiteElo").map(|s| s.i64()).transpose()?;
+    let white_rating_diff_col = optional_column(df, "WhiteRatingDiff").map(|s| s.f64()).transpose()?;
+    let white_tos_violation_col = optional_column(df, "White_tosViolation").map(|s| s.bool()).transpose()?;
+    let white_play_time_total_col = optional_column(df, "White_playTime_total").map(|s| s.f64()).transpose()?;
+    let white_count_all_col = optional_column(df, "White_count_all").map(|s| s.f64()).transpose()?;
+    let black_elo_col = optional_column(df, "BlackElo").map(|s| s.i64()).transpose()?;
+    let black_rating_diff_col = optional_column(df, "BlackRatingDiff").map(|s| s.f64()).transpose()?;
+    let black_tos_violation_col = optional_column(df, "Black_tosViolation").map(|s| s.bool()).transpose()?;
+    let black_play_time_total_col = optional_column(df, "Black_playTime_total").map(|s| s.f64()).transpose()?;
+    let black_count_all_col = optional_column(df, "Black_count_all").map(|s| s.f64()).transpose()?;
+    let moves_col = optional_column(df, "Moves").map(|s| s.utf8()).transpose()?;
+    let total_moves_col = optional_column(df, "TotalMoves").map(|s| s.i64()).transpose()?;
+    let eco_col = optional_column(df, "ECO").map(|s| s.utf8()).transpose()?;
+    let opening_col = optional_column(df, "Opening").map(|s| s.utf8()).transpose()?;
+    let time_control_col = optional_column(df, "TimeControl").map(|s| s.utf8()).transpose()?;
+    let date_col = optional_column(df, "Date")
+        .or_else(|| optional_column(df, "UTCDate"))
+        .map(|s| s.utf8())
+        .transpose()?;
 
     for idx in 0..row_count {
         let game = Game {
-            game_id: game_id_col.get(idx).unwrap_or_default().to_string(),
-            event: event_col.get(idx).unwrap_or_default().to_string(),
+            game_id: game_id_col.and_then(|c| c.get(idx)).unwrap_or_default().to_string(),
+            event: event_col.and_then(|c| c.get(idx)).unwrap_or_default().to_string(),
             white: white_col.get(idx).unwrap_or_default().to_string(),
-            white_elo: white_elo_col.get(idx).map(|v| v as u32),
-            white_rating_diff: white_rating_diff_col.get(idx).map(|v| v as f32),
-            white_tos_violation: white_tos_violation_col.get(idx),
-            white_play_time_total: white_play_time_total_col.get(idx).map(|v| v.to_string()),
-            white_count_all: white_count_all_col.get(idx).map(|v| v as u32), 
+            white_elo: white_elo_col.and_then(|c| c.get(idx)).map(|v| v as u32),
+            white_rating_diff: white_rating_diff_col.and_then(|c| c.get(idx)).map(|v| v as f32),
+            white_tos_violation: white_tos_violation_col.and_then(|c| c.get(idx)),
+            white_play_time_total: white_play_time_total_col.and_then(|c| c.get(idx)).map(|v| v.to_string()),
+            white_count_all: white_count_all_col.and_then(|c| c.get(idx)).map(|v| v as u32),
             black: black_col.get(idx).unwrap_or_default().to_string(),
-            black_elo: black_elo_col.get(idx).map(|v| v as u32),
-            black_rating_diff: black_rating_diff_col.get(idx).map(|v| v as f32),
-            black_tos_violation: black_tos_violation_col.get(idx),
-            black_play_time_total: black_play_time_total_col.get(idx).map(|v| v.to_string()),
-            black_count_all: black_count_all_col.get(idx).map(|v| v as u32), 
-            moves: moves_col.get(idx).unwrap_or_default().to_string(),
-            total_moves: total_moves_col.get(idx).map(|v| v as u32),
-            eco: eco_col.get(idx).unwrap_or_default().to_string(),
-            opening: opening_col.get(idx).unwrap_or_default().to_string(),
-            time_control: time_control_col.get(idx).unwrap_or_default().to_string(),
+            black_elo: black_elo_col.and_then(|c| c.get(idx)).map(|v| v as u32),
+            black_rating_diff: black_rating_diff_col.and_then(|c| c.get(idx)).map(|v| v as f32),
+            black_tos_violation: black_tos_violation_col.and_then(|c| c.get(idx)),
+            black_play_time_total: black_play_time_total_col.and_then(|c| c.get(idx)).map(|v| v.to_string()),
+            black_count_all: black_count_all_col.and_then(|c| c.get(idx)).map(|v| v as u32),
+            moves: moves_col.and_then(|c| c.get(idx)).unwrap_or_default().to_string(),
+            total_moves: total_moves_col.and_then(|c| c.get(idx)).map(|v| v as u32),
+            eco: eco_col.and_then(|c| c.get(idx)).unwrap_or_default().to_string(),
+            opening: opening_col.and_then(|c| c.get(idx)).unwrap_or_default().to_string(),
+            time_control: time_control_col.and_then(|c| c.get(idx)).unwrap_or_default().to_string(),
             result: result_col.get(idx).unwrap_or_default().to_string(),
+            date: date_col.and_then(|c| c.get(idx)).map(|v| v.to_string()),
         };
         games.push(game);
     }
@@ -121,237 +369,1907 @@ pub fn read_games_from_dataframe(df: &DataFrame) -> Result<Vec<Game>, Box<dyn Er
     Ok(games)
 }
 
+// One JSON object per line, keyed as described on `Game`. Unknown keys are
+// ignored and blank lines skipped; a line that isn't a valid record fails
+// the whole read with its line number. `.gz` files are decompressed.
+pub fn read_games_from_ndjson(path: &str) -> Result<Vec<Game>, AnalysisError> {
+    let reader = crate::input::open_input(std::path::Path::new(path))?;
+    let mut games = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let game = serde_json::from_str(&line)
+            .map_err(|err| AnalysisError::Parse(format!("{} line {}: {}", path, index + 1, err)))?;
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+
+/// How games are turned into edges.
+///
+/// `Directed` adds a winner -> loser edge per decisive game, with draws
+/// handled by `DrawPolicy`. `Undirected` adds every edge in both directions,
+/// so the graph only records who played whom. The graph stays a `DiGraph`
+/// either way, so every centrality function accepts both. In undirected mode
+/// closeness counts paths in either direction, which raises it for players
+/// who mostly won or mostly lost, and betweenness no longer depends on who
+/// won, so bridge players between clusters stand out regardless of results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphMode {
+    #[default]
+    Directed,
+    Undirected,
+}
+
+/// How player names are matched before they become graph nodes or
+/// performance entries. Exports keep the first spelling seen for each player,
+/// trimmed, so merged players still read naturally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameNormalization {
+    #[default]
+    Exact,
+    Trim,
+    TrimAndCaseFold,
+}
+
+impl NameNormalization {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            NameNormalization::Exact => name.to_string(),
+            NameNormalization::Trim => name.trim().to_string(),
+            NameNormalization::TrimAndCaseFold => normalize_name(name),
+        }
+    }
+}
+
+pub fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+// Maps each normalized name to the display name first seen for it. Every
+// game's White and then Black is read up front, the order the graph adds its
+// nodes in, so an aggregation that skips some games still uses the node names.
+pub(crate) struct PlayerNames {
+    normalization: NameNormalization,
+    display_names: HashMap<String, String>,
+}
+
+impl PlayerNames {
+    pub(crate) fn new(games: &[Game], normalization: NameNormalization) -> PlayerNames {
+        let mut display_names = HashMap::new();
+        if normalization != NameNormalization::Exact {
+            for game in games {
+                for name in [&game.white, &game.black] {
+                    display_names
+                        .entry(normalization.apply(name))
+                        .or_insert_with(|| name.trim().to_string());
+                }
+            }
+        }
+        PlayerNames { normalization, display_names }
+    }
+
+    pub(crate) fn display_name(&self, name: &str) -> String {
+        if self.normalization == NameNormalization::Exact {
+            return name.to_string();
+        }
+        self.display_names
+            .get(&self.normalization.apply(name))
+            .cloned()
+            .unwrap_or_else(|| name.trim().to_string())
+    }
+}
+
+/// What a drawn game contributes to the graph. `Bidirectional` adds an edge
+/// each way, since neither player beat the other; `Skip` leaves draws out so
+/// only decisive games shape the rankings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawPolicy {
+    Skip,
+    #[default]
+    Bidirectional,
+}
 
+/// How repeat games between the same two players are stored. `Collapsed`
+/// keeps one edge per direction whose weight counts the games; `Multi` adds a
+/// parallel edge of weight 1 for every game.
+///
+/// The weighted measures (eigenvector, Katz, strength) and the structural
+/// ones (reciprocity, dominance chains, intransitive triples, shortest paths,
+/// graph diffs) give the same answer either way. PageRank ignores weights, so
+/// only `Multi` lets repeat wins count for more. Betweenness, closeness and
+/// the weighted betweenness/closeness expect `Collapsed`: parallel edges
+/// multiply the shortest path counts and the game-count distances assume the
+/// weight holds every game. In/out degree counts opponents when collapsed and
+/// games when not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeMode {
+    Multi,
+    #[default]
+    Collapsed,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphOptions {
+    pub mode: GraphMode,
+    pub names: NameNormalization,
+    pub draws: DrawPolicy,
+    pub edges: EdgeMode,
+    /// Sources sampled for approximate betweenness, see
+    /// `calculate_betweenness_centrality`. Doesn't change the graph itself.
+    pub betweenness_samples: Option<usize>,
+    /// Seed for anything picked at random: the betweenness sources, and the
+    /// row order when the input is shuffled before splitting. `None` uses
+    /// `DEFAULT_SAMPLE_SEED`, so runs are reproducible either way.
+    pub seed: Option<u64>,
+    /// Half-life, in games, for the recency-weighted PageRank, see
+    /// `build_graph_recency_weighted_with`. `None` skips it.
+    pub recency_half_life: Option<usize>,
+}
+
+pub const DEFAULT_SAMPLE_SEED: u64 = 210;
+
+impl GraphOptions {
+    pub fn sample_seed(&self) -> u64 {
+        self.seed.unwrap_or(DEFAULT_SAMPLE_SEED)
+    }
+}
+
+// The binary always builds with its configured options; this is the plain
+// entry point for callers that want the default graph.
+#[allow(dead_code)]
 pub fn build_graph(games: &[Game]) -> DiGraph<String, u32> {
+    build_graph_with(games, &GraphOptions::default())
+}
+
+pub fn build_graph_with(games: &[Game], options: &GraphOptions) -> DiGraph<String, u32> {
     let mut graph = DiGraph::new();
     let mut player_indices = HashMap::new();
+    let mut progress = Progress::new("Building player graph");
 
     for game in games {
-        let white_index = *player_indices
-            .entry(game.white.clone())
-            .or_insert_with(|| graph.add_node(game.white.clone()));
-        let black_index = *player_indices
-            .entry(game.black.clone())
-            .or_insert_with(|| graph.add_node(game.black.clone()));
-
-        graph.add_edge(white_index, black_index, 1);
+        add_game(&mut graph, &mut player_indices, game, options);
+        progress.tick();
     }
+    progress.finish();
 
     graph
 }
 
+/// Folds one more game into a graph, so games can be added as they arrive
+/// instead of rebuilding the graph each time. `player_indices` maps each
+/// player's normalized name to their node and must start out empty alongside
+/// an empty graph, then be passed back in with it, and the same `options`,
+/// every time.
+pub fn add_game(graph: &mut DiGraph<String, u32>, player_indices: &mut HashMap<String, NodeIndex>, game: &Game, options: &GraphOptions) {
+    let white_index = player_node(graph, player_indices, &game.white, options.names);
+    let black_index = player_node(graph, player_indices, &game.black, options.names);
+    add_game_edges(graph, white_index, black_index, GameResult::from_game(game), options);
+}
+
+// A new player's node keeps the first spelling seen, trimmed unless names are
+// matched exactly.
+fn player_node<E>(graph: &mut DiGraph<String, E>, player_indices: &mut HashMap<String, NodeIndex>, name: &str, normalization: NameNormalization) -> NodeIndex {
+    let display_name = match normalization {
+        NameNormalization::Exact => name,
+        _ => name.trim(),
+    };
+    *player_indices
+        .entry(normalization.apply(name))
+        .or_insert_with(|| graph.add_node(display_name.to_string()))
+}
+
+/// Builds the same graph as `build_graph_with` on the games of
+/// `read_games_from_dataframe`, straight from the White, Black and Result
+/// columns. Results are read with the scheme `detect_result_scheme` picks, as
+/// `load_games` does. Player names are borrowed from the DataFrame while
+/// indexing, so each name is copied once into its node rather than once per
+/// game into a `Game` and again into the index map, and no per-row struct with
+/// its dozen other fields is allocated at all.
+pub fn build_graph_from_dataframe(df: &DataFrame, options: &GraphOptions) -> Result<DiGraph<String, u32>, AnalysisError> {
+    let white_col = column(df, "White")?.utf8()?;
+    let black_col = column(df, "Black")?.utf8()?;
+    // A Result column of plain scores is read in as numbers.
+    let result_series = column(df, "Result")?.cast(&DataType::Utf8)?;
+    let result_col = result_series.utf8()?;
+    let white_rating_diff_col = optional_column(df, "WhiteRatingDiff").map(|s| s.f64()).transpose()?;
+    let black_rating_diff_col = optional_column(df, "BlackRatingDiff").map(|s| s.f64()).transpose()?;
+    let result_parts = |idx: usize| {
+        (
+            result_col.get(idx).unwrap_or_default(),
+            white_rating_diff_col.and_then(|c| c.get(idx)).map(|v| v as f32),
+            black_rating_diff_col.and_then(|c| c.get(idx)).map(|v| v as f32),
+        )
+    };
+    let scheme = detect_result_scheme_from_parts((0..df.height()).map(result_parts));
 
-pub fn calculate_pagerank(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
-    let mut pr = Pagerank::new();
-    let mut node_indices = HashMap::new();
+    let mut graph = DiGraph::new();
+    let mut player_indices: HashMap<Cow<str>, NodeIndex> = HashMap::new();
+    let mut progress = Progress::new("Building player graph");
+
+    for (idx, (white, black)) in white_col.into_iter().zip(black_col).enumerate() {
+        let white_index = dataframe_player_node(&mut graph, &mut player_indices, white.unwrap_or_default(), options.names);
+        let black_index = dataframe_player_node(&mut graph, &mut player_indices, black.unwrap_or_default(), options.names);
 
-    for node_index in graph.node_indices() {
-        node_indices.insert(graph[node_index].clone(), node_index.index() as u64);
+        let (result, white_rating_diff, black_rating_diff) = result_parts(idx);
+        // What `normalize_results` leaves alone is still read as `from_game` would.
+        let result = GameResult::from_parts_with(scheme, result, white_rating_diff, black_rating_diff)
+            .or_else(|| GameResult::from_parts(result, white_rating_diff, black_rating_diff));
+        add_game_edges(&mut graph, white_index, black_index, result, options);
+        progress.tick();
     }
+    progress.finish();
 
-    for edge in graph.edge_references() {
-        let source_index = node_indices[&graph[edge.source()]];
-        let target_index = node_indices[&graph[edge.target()]];
-        pr.add_edge(source_index.to_string(), target_index.to_string());
+    Ok(graph)
+}
+
+// Names are only copied for the key when normalization changes them.
+fn dataframe_player_node<'a>(
+    graph: &mut DiGraph<String, u32>,
+    player_indices: &mut HashMap<Cow<'a, str>, NodeIndex>,
+    name: &'a str,
+    normalization: NameNormalization,
+) -> NodeIndex {
+    let (key, display_name) = match normalization {
+        NameNormalization::Exact => (Cow::Borrowed(name), name),
+        _ => (Cow::Owned(normalization.apply(name)), name.trim()),
+    };
+    *player_indices
+        .entry(key)
+        .or_insert_with(|| graph.add_node(display_name.to_string()))
+}
+
+// Players are always added as nodes, but a game whose result can't be read
+// has no winner to point from, so it adds no edges.
+fn add_game_edges(graph: &mut DiGraph<String, u32>, white: NodeIndex, black: NodeIndex, result: Option<GameResult>, options: &GraphOptions) {
+    let (winner, loser) = match result {
+        Some(GameResult::WhiteWin) => (white, black),
+        Some(GameResult::BlackWin) => (black, white),
+        Some(GameResult::Draw) => {
+            if options.draws == DrawPolicy::Bidirectional {
+                add_game_edge(graph, white, black, options.edges);
+                add_game_edge(graph, black, white, options.edges);
+            }
+            return;
+        }
+        None => return,
+    };
+
+    add_game_edge(graph, winner, loser, options.edges);
+    if options.mode == GraphMode::Undirected {
+        add_game_edge(graph, loser, winner, options.edges);
+    }
+}
+
+fn add_game_edge(graph: &mut DiGraph<String, u32>, source: NodeIndex, target: NodeIndex, edges: EdgeMode) {
+    match edges {
+        EdgeMode::Collapsed => add_or_increment_edge(graph, source, target),
+        EdgeMode::Multi => {
+            graph.add_edge(source, target, 1);
+        }
+    }
+}
+
+// Repeat pairings share one edge whose weight counts the games played.
+fn add_or_increment_edge(graph: &mut DiGraph<String, u32>, source: NodeIndex, target: NodeIndex) {
+    match graph.find_edge(source, target) {
+        Some(edge) => graph[edge] += 1,
+        None => {
+            graph.add_edge(source, target, 1);
+        }
     }
+}
+
+/// `build_graph_recency_weighted_with` with the default options.
+#[allow(dead_code)]
+pub fn build_graph_recency_weighted(games: &[Game], half_life_games: usize) -> DiGraph<String, f64> {
+    build_graph_recency_weighted_with(games, half_life_games, &GraphOptions::default())
+}
+
+/// Like `build_graph_with`, but each game adds a weight that decays
+/// exponentially with its age instead of a count of 1, so recent results
+/// dominate. `games` must be oldest first: a game's age is how many games come
+/// after it in the input, and it adds `0.5^(age / half_life_games)` to its
+/// edge. The last game weighs 1, the game `half_life_games` before it 0.5, and
+/// so on. Names, draws and the graph mode follow `options`, so the nodes are
+/// the ones `build_graph_with` makes for the same games. Repeat games between
+/// two players always add to one edge whatever `options.edges` says. Games
+/// with an unreadable result add no edge but still age the ones before them.
+/// A half-life of 0 is treated as 1.
+pub fn build_graph_recency_weighted_with(games: &[Game], half_life_games: usize, options: &GraphOptions) -> DiGraph<String, f64> {
+    let mut graph = DiGraph::new();
+    let mut player_indices = HashMap::new();
+    let half_life = half_life_games.max(1) as f64;
 
-    pr.calculate();
-    let mut pagerank_scores = HashMap::new();
+    for (position, game) in games.iter().enumerate() {
+        let white = player_node(&mut graph, &mut player_indices, &game.white, options.names);
+        let black = player_node(&mut graph, &mut player_indices, &game.black, options.names);
+        let age = (games.len() - 1 - position) as f64;
+        let weight = 0.5f64.powf(age / half_life);
 
-    for (node_index, score) in pr.nodes().iter() {
-        if let Some(index) = graph.node_indices().find(|&i| i.index() == node_index.parse::<usize>().unwrap()) {
-            pagerank_scores.insert(index, *score);
+        let undirected = options.mode == GraphMode::Undirected;
+        let edges = match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) if undirected => vec![(white, black), (black, white)],
+            Some(GameResult::WhiteWin) => vec![(white, black)],
+            Some(GameResult::BlackWin) if undirected => vec![(black, white), (white, black)],
+            Some(GameResult::BlackWin) => vec![(black, white)],
+            Some(GameResult::Draw) if options.draws == DrawPolicy::Bidirectional => vec![(white, black), (black, white)],
+            Some(GameResult::Draw) | None => Vec::new(),
+        };
+        for (source, target) in edges {
+            match graph.find_edge(source, target) {
+                Some(edge) => graph[edge] += weight,
+                None => {
+                    graph.add_edge(source, target, weight);
+                }
+            }
         }
     }
 
-    pagerank_scores
+    graph
 }
 
 
-pub fn calculate_betweenness_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
-    let num_samples = graph.node_count();
-    let centrality_scores = betweenness_centrality(graph, true, true, num_samples);
-    graph.node_indices().zip(centrality_scores.into_iter()).filter_map(|(i, s)| s.map(|score| (i, score))).collect()
+/// Edges point from winner to loser, so rank flows towards the players who
+/// were beaten. A high score therefore means a player lost to many players
+/// who themselves lost to others, and the strongest players score low. With
+/// bidirectional draws, drawn opponents share rank both ways.
+pub fn calculate_pagerank(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
+    calculate_pagerank_with(graph, DEFAULT_PAGERANK_DAMPING, DEFAULT_PAGERANK_MAX_ITERATIONS)
 }
 
-pub fn calculate_closeness_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
-    let centrality_scores = closeness_centrality(graph, true);
-    graph.node_indices().zip(centrality_scores.into_iter()).filter_map(|(i, s)| s.map(|score| (i, score))). collect()
+/// Damping factor used by `calculate_pagerank`, the usual 0.85.
+pub const DEFAULT_PAGERANK_DAMPING: f64 = 0.85;
+/// Iteration cap used by `calculate_pagerank`.
+pub const DEFAULT_PAGERANK_MAX_ITERATIONS: usize = 100;
+
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+
+// Power iteration on the unnormalized form simple_pagerank used,
+// PR(u) = (1 - d) + d * sum(PR(v) / out(v)) over edges v -> u, so scores
+// average around 1 rather than summing to 1. Every collapsed edge counts
+// once whatever its weight, and players who never won (no out-edges) don't
+// pass their rank on. Stops once the total change drops below the tolerance
+// or after `max_iter` rounds, whichever comes first.
+pub fn calculate_pagerank_with(graph: &DiGraph<String, u32>, damping: f64, max_iter: usize) -> HashMap<NodeIndex, f64> {
+    pagerank_iteration(graph, damping, max_iter, |_| 1.0)
 }
 
-pub fn export_centrality_data(centrality_scores: &HashMap<NodeIndex, f64>, graph: &DiGraph<String, u32>, filepath: &str) -> Result<(), Box<dyn Error>> {
-    let file = OpenOptions::new().write(true).create(true).open(filepath)?;
-    let mut wtr = Writer::from_writer(BufWriter::new(file));
-    for (node, &score) in centrality_scores.iter() {
-        wtr.serialize((graph[*node].clone(), score))?;
+/// Like `calculate_pagerank`, but each player passes their rank on in
+/// proportion to how many games they won against each opponent, so beating
+/// someone ten times sends them ten times the rank of beating someone once.
+pub fn calculate_weighted_pagerank(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
+    pagerank_iteration(graph, DEFAULT_PAGERANK_DAMPING, DEFAULT_PAGERANK_MAX_ITERATIONS, |weight| weight as f64)
+}
+
+/// PageRank over a `build_graph_recency_weighted` graph, where rank is split
+/// by the decayed weights so recent wins pass on more of it than old ones.
+pub fn calculate_recency_pagerank(graph: &DiGraph<String, f64>) -> HashMap<NodeIndex, f64> {
+    pagerank_iteration(graph, DEFAULT_PAGERANK_DAMPING, DEFAULT_PAGERANK_MAX_ITERATIONS, |weight| weight)
+}
+
+// Rank leaves a player split across their out-edges by `edge_share`, relative
+// to the total share of all their out-edges.
+fn pagerank_iteration<W: Copy>(graph: &DiGraph<String, W>, damping: f64, max_iter: usize, edge_share: impl Fn(W) -> f64) -> HashMap<NodeIndex, f64> {
+    let node_count = graph.node_count();
+    let out_totals: Vec<f64> = graph
+        .node_indices()
+        .map(|node| graph.edges(node).map(|edge| edge_share(*edge.weight())).sum())
+        .collect();
+    let mut scores = vec![1.0; node_count];
+
+    for _ in 0..max_iter {
+        let mut next_scores = vec![1.0 - damping; node_count];
+        for edge in graph.edge_references() {
+            let source = edge.source().index();
+            next_scores[edge.target().index()] += damping * scores[source] * edge_share(*edge.weight()) / out_totals[source];
+        }
+
+        let change: f64 = scores.iter().zip(&next_scores).map(|(old, new)| (old - new).abs()).sum();
+        scores = next_scores;
+        if change < PAGERANK_TOLERANCE {
+            break;
+        }
     }
-    wtr.flush()?;
-    Ok(())
+
+    graph.node_indices().zip(scores).collect()
 }
 
-pub fn export_performance(performance: &HashMap<String, PlayerPerformance>, filepath: &str) -> Result<(), Box<dyn Error>> {
-    let file = OpenOptions::new().write(true).create(true).open(filepath)?;
-    let mut wtr = Writer::from_writer(BufWriter::new(file));
-    for (player, stats) in performance.iter() {
-        wtr.serialize((
-            player.clone(),
-            stats.games_played,
-            stats.games_won,
-            stats.games_lost,
-            stats.games_drawn,
-            stats.total_rating_change,
-            stats.win_rate,
-        ))?;
+
+/// Exact betweenness runs a shortest-path search from every player, which
+/// takes O(V·E) time and gets slow on big player pools. With `samples` set to
+/// k it searches from only k sources picked at random and scales the totals
+/// up by V/k: about V/k times faster, at the cost of an estimate whose error
+/// shrinks as k grows. The sources are drawn from an RNG seeded with `seed`,
+/// so the same graph, k and seed always give the same scores. `None`, or k of
+/// at least V, is exact and ignores the seed.
+pub fn calculate_betweenness_centrality(graph: &DiGraph<String, u32>, samples: Option<usize>, seed: u64) -> HashMap<NodeIndex, f64> {
+    let node_count = graph.node_count();
+    match samples {
+        Some(samples) if samples < node_count => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let sources: Vec<NodeIndex> = rand::seq::index::sample(&mut rng, node_count, samples)
+                .into_iter()
+                .map(NodeIndex::new)
+                .collect();
+            sampled_betweenness_centrality(graph, &sources)
+        }
+        _ => {
+            let centrality_scores = betweenness_centrality(graph, true, true, BETWEENNESS_PARALLEL_THRESHOLD);
+            graph.node_indices().zip(centrality_scores).filter_map(|(i, s)| s.map(|score| (i, score))).collect()
+        }
     }
-    wtr.flush()?;
-    Ok(())
 }
 
-pub fn track_player_performance(games: &[Game]) -> HashMap<String, PlayerPerformance> {
-    let mut white_performance: HashMap<String, PlayerPerformance> = HashMap::new();
-    let mut black_performance: HashMap<String, PlayerPerformance> = HashMap::new();
+// Graphs with at least this many players are searched in parallel.
+const BETWEENNESS_PARALLEL_THRESHOLD: usize = 50;
 
-    for game in games {
-        let (white_result, black_result) = match game.result.as_str() {
-            "Normal" => {
-                if game.white_rating_diff.unwrap_or(0.0) > 0.0 {
-                    ("1-0", "0-1")
-                } else if game.black_rating_diff.unwrap_or(0.0) > 0.0 {
-                    ("0-1", "1-0")
-                } else {
-                    ("1/2-1/2", "1/2-1/2")
+// Brandes' algorithm from `sources` only, counting endpoints and normalized
+// like the exact scores once the totals are scaled up to every source.
+fn sampled_betweenness_centrality(graph: &DiGraph<String, u32>, sources: &[NodeIndex]) -> HashMap<NodeIndex, f64> {
+    let mut betweenness: HashMap<NodeIndex, f64> = graph.node_indices().map(|node| (node, 0.0)).collect();
+
+    for &source in sources {
+        let mut order = Vec::new();
+        let mut predecessors: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        let mut path_counts: HashMap<NodeIndex, f64> = HashMap::from([(source, 1.0)]);
+        let mut distances: HashMap<NodeIndex, usize> = HashMap::from([(source, 0)]);
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            let distance = distances[&node];
+            for neighbor in graph.neighbors(node) {
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
                 }
-            }
-            "Time forfeit" => {
-                if game.white_rating_diff.unwrap_or(0.0) > 0.0 {
-                    ("1-0", "0-1")
-                } else {
-                    ("0-1", "1-0")
+                if distances[&neighbor] == distance + 1 {
+                    *path_counts.entry(neighbor).or_insert(0.0) += path_counts[&node];
+                    predecessors.entry(neighbor).or_default().push(node);
                 }
             }
-            _ => continue,
-        };
+        }
 
-        let white_entry = white_performance.entry(game.white.clone()).or_insert_with(PlayerPerformance::default);
-        let black_entry = black_performance.entry(game.black.clone()).or_insert_with(PlayerPerformance::default);
+        *betweenness.get_mut(&source).unwrap() += (order.len() - 1) as f64;
+        let mut dependency: HashMap<NodeIndex, f64> = HashMap::new();
+        while let Some(node) = order.pop() {
+            let node_dependency = dependency.get(&node).copied().unwrap_or(0.0);
+            let coefficient = (1.0 + node_dependency) / path_counts[&node];
+            for predecessor in predecessors.get(&node).into_iter().flatten() {
+                *dependency.entry(*predecessor).or_insert(0.0) += path_counts[predecessor] * coefficient;
+            }
+            if node != source {
+                *betweenness.get_mut(&node).unwrap() += node_dependency + 1.0;
+            }
+        }
+    }
 
-        white_entry.update(white_result, game.white_rating_diff.unwrap_or(0.0));
-        black_entry.update(black_result, game.black_rating_diff.unwrap_or(0.0));
+    let node_count = graph.node_count();
+    if node_count >= 2 && !sources.is_empty() {
+        let scale = node_count as f64 / sources.len() as f64 / (node_count * (node_count - 1)) as f64;
+        for score in betweenness.values_mut() {
+            *score *= scale;
+        }
     }
 
-    white_performance.into_iter().chain(black_performance.into_iter()).collect()
+    betweenness
 }
 
-pub fn calculate_in_out_degree_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, (usize, usize)> {
-    let mut in_out_degree_centrality = HashMap::new();
+pub fn calculate_closeness_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
+    let centrality_scores = closeness_centrality(graph, true);
+    graph.node_indices().zip(centrality_scores.into_iter()).filter_map(|(i, s)| s.map(|score| (i, score))). collect()
+}
+
+// Sum of 1/d(v, u) over every other node v that can reach u, so players in
+// separate components still get a finite score.
+pub fn calculate_harmonic_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
+    let mut harmonic_scores = HashMap::new();
 
     for node in graph.node_indices() {
-        let in_degree = graph.neighbors_directed(node, petgraph::Direction::Incoming).count();
-        let out_degree = graph.neighbors_directed(node, petgraph::Direction::Outgoing).count();
-        in_out_degree_centrality.insert(node, (in_degree, out_degree));
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(node, 0u32);
+        queue.push_back(node);
+        // Summed in visit order so the score doesn't depend on hash order.
+        let mut score = 0.0;
+
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[&current];
+            for neighbor in graph.neighbors_directed(current, petgraph::Direction::Incoming) {
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
+                    score += 1.0 / (distance + 1) as f64;
+                }
+            }
+        }
+
+        harmonic_scores.insert(node, score);
     }
 
-    in_out_degree_centrality
+    harmonic_scores
 }
 
-pub fn calculate_weighted_centrality(graph: &DiGraph<String, u32>) -> (HashMap<NodeIndex, f64>, HashMap<NodeIndex, f64>) {
-    let weighted_betweenness = betweenness_centrality(graph, true, true, graph.node_count());
-    let weighted_closeness = closeness_centrality(graph, true);
+pub fn calculate_eigenvector_centrality(graph: &DiGraph<String, u32>) -> Result<HashMap<NodeIndex, f64>, AnalysisError> {
+    let centrality_scores = eigenvector_centrality(graph, |edge| Ok::<f64, AnalysisError>(*edge.weight() as f64), None, None)?
+        .ok_or_else(|| AnalysisError::Convergence("eigenvector centrality".to_string()))?;
+    Ok(graph.node_indices().zip(centrality_scores).collect())
+}
 
-    let weighted_betweenness_scores: HashMap<NodeIndex, f64> = graph
-        .node_indices()
-        .zip(weighted_betweenness.into_iter())
-        .filter_map(|(i, s)| s.map(|score| (i, score)))
-        .collect();
+/// Attenuation used by the pipeline for Katz centrality. The iteration only
+/// converges when `alpha` is below 1/λ, where λ is the largest eigenvalue of
+/// the adjacency matrix; with game-count weights λ grows with the busiest
+/// pairings, so keep this small (0.1 suits graphs whose players rarely meet
+/// more than a handful of times) and lower it if convergence fails.
+pub const DEFAULT_KATZ_ALPHA: f64 = 0.1;
+/// Baseline score every player receives regardless of their connections.
+pub const DEFAULT_KATZ_BETA: f64 = 1.0;
 
-    let weighted_closeness_scores: HashMap<NodeIndex, f64> = graph
-        .node_indices()
-        .zip(weighted_closeness.into_iter())
-        .filter_map(|(i, s)| s.map(|score| (i, score)))
-        .collect();
+const KATZ_MAX_ITERATIONS: usize = 1000;
+const KATZ_TOLERANCE: f64 = 1e-6;
+
+// x(v) = alpha * sum over edges u -> v of w(u, v) * x(u) + beta, iterated until
+// the scores stop moving, then scaled to unit length like eigenvector
+// centrality so the two are comparable.
+pub fn calculate_katz_centrality(graph: &DiGraph<String, u32>, alpha: f64, beta: f64) -> Result<HashMap<NodeIndex, f64>, AnalysisError> {
+    let node_count = graph.node_count();
+    if node_count == 0 {
+        return Ok(HashMap::new());
+    }
 
-    (weighted_betweenness_scores, weighted_closeness_scores)
+    let mut scores = vec![0.0; node_count];
+    for _ in 0..KATZ_MAX_ITERATIONS {
+        let mut next = vec![0.0; node_count];
+        for node in graph.node_indices() {
+            let incoming: f64 = graph
+                .edges_directed(node, Direction::Incoming)
+                .map(|edge| *edge.weight() as f64 * scores[edge.source().index()])
+                .sum();
+            next[node.index()] = alpha * incoming + beta;
+        }
+
+        let change: f64 = next.iter().zip(scores.iter()).map(|(new, old)| (new - old).abs()).sum();
+        scores = next;
+        if change < node_count as f64 * KATZ_TOLERANCE {
+            let norm = scores.iter().map(|score| score * score).sum::<f64>().sqrt();
+            let norm = if norm > 0.0 { norm } else { 1.0 };
+            return Ok(graph.node_indices().map(|node| (node, scores[node.index()] / norm)).collect());
+        }
+    }
+
+    Err(AnalysisError::Convergence("Katz centrality".to_string()))
 }
 
-pub fn export_in_out_degree_centrality(
-    in_out_degree_centrality: &HashMap<NodeIndex, (usize, usize)>,
-    graph: &DiGraph<String, u32>,
-    filepath: &str,
-) -> Result<(), Box<dyn Error>> {
-    let file = OpenOptions::new().write(true).create(true).open(filepath)?;
+pub fn export_centrality_data(centrality_scores: &HashMap<NodeIndex, f64>, graph: &DiGraph<String, u32>, filepath: &str, top_n: Option<usize>) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
     let mut wtr = Writer::from_writer(BufWriter::new(file));
-    for (node, &(in_degree, out_degree)) in in_out_degree_centrality.iter() {
-        wtr.serialize((graph[*node].clone(), in_degree, out_degree))?;
+    for (player, score) in sorted_scores(centrality_scores, graph, top_n) {
+        wtr.serialize((player, score))?;
     }
     wtr.flush()?;
     Ok(())
 }
 
-pub fn export_weighted_centrality(
-    weighted_betweenness: &HashMap<NodeIndex, f64>,
-    weighted_closeness: &HashMap<NodeIndex, f64>,
-    graph: &DiGraph<String, u32>,
-    filepath: &str,
-) -> Result<(), Box<dyn Error>> {
-    let file = OpenOptions::new().write(true).create(true).open(filepath)?;
+// Min-max scales scores onto 0-100 as an "influence rating". When every score
+// is the same there is no spread to scale, so everyone gets the midpoint.
+pub fn normalize_scores(scores: &HashMap<NodeIndex, f64>) -> HashMap<NodeIndex, f64> {
+    let min = scores.values().copied().fold(f64::INFINITY, f64::min);
+    let max = scores.values().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    scores
+        .iter()
+        .map(|(&node, &score)| {
+            let normalized = if range > 0.0 { (score - min) / range * 100.0 } else { 50.0 };
+            (node, normalized)
+        })
+        .collect()
+}
+
+// Highest scoring player, ties going to the name that sorts first. None when
+// there are no scores.
+pub fn top_player<'a>(scores: &HashMap<NodeIndex, f64>, graph: &'a DiGraph<String, u32>) -> Option<(&'a str, f64)> {
+    scores
+        .iter()
+        .map(|(&node, &score)| (graph[node].as_str(), score))
+        .min_by(|(player_a, a), (player_b, b)| b.total_cmp(a).then_with(|| player_a.cmp(player_b)))
+}
+
+// Same rows as `export_centrality_data` with the 0-100 influence rating as a
+// third column.
+pub fn export_centrality_data_with_normalized(centrality_scores: &HashMap<NodeIndex, f64>, graph: &DiGraph<String, u32>, filepath: &str, top_n: Option<usize>) -> Result<(), AnalysisError> {
+    let normalized = normalize_scores(centrality_scores);
+    let names: HashMap<&String, NodeIndex> = centrality_scores.keys().map(|&node| (&graph[node], node)).collect();
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
     let mut wtr = Writer::from_writer(BufWriter::new(file));
-    for (node, &betweenness) in weighted_betweenness.iter() {
-        let closeness = weighted_closeness[node];
-        wtr.serialize((graph[*node].clone(), betweenness, closeness))?;
+    for (player, score) in sorted_scores(centrality_scores, graph, top_n) {
+        wtr.serialize((player, score, normalized[&names[player]]))?;
     }
     wtr.flush()?;
     Ok(())
 }
 
-pub fn export_mean_mode_metrics(
-    mean_mode_metrics: &HashMap<String, (f64, f64, f64, u32)>,
-    filepath: &str,
-) -> Result<(), Box<dyn Error>> {
-    let file = OpenOptions::new().write(true).create(true).open(filepath)?;
+pub fn export_eigenvector_centrality(eigenvector_scores: &HashMap<NodeIndex, f64>, graph: &DiGraph<String, u32>, filepath: &str, top_n: Option<usize>) -> Result<(), AnalysisError> {
+    export_centrality_data(eigenvector_scores, graph, filepath, top_n)
+}
+
+// Player names are quoted DOT IDs, where a backslash escapes the character
+// after it, so backslashes are doubled before quotes are escaped; the other
+// way round would double the backslash added in front of each quote.
+pub fn export_graph_dot(graph: &DiGraph<String, u32>, filepath: &str) -> Result<(), AnalysisError> {
+    let dot = Dot::with_attr_getters(
+        graph,
+        &[Config::NodeNoLabel, Config::EdgeNoLabel],
+        &|_, edge| format!("label = \"{}\"", edge.weight()),
+        &|_, (_, player)| format!("label = \"{}\"", player.replace('\\', "\\\\").replace('"', "\\\"")),
+    );
+    let mut file = BufWriter::new(File::create(filepath)?);
+    write!(file, "{:?}", dot)?;
+    file.flush()?;
+    Ok(())
+}
+
+// The graph as a square CSV: players in node order across the header and
+// down the first column, and each cell the total weight of the edges from
+// the row's player to the column's player (with the default graph, the games
+// the row player won against the column player plus their draws), 0 where
+// there are none. Parallel edges are summed, so multi and collapsed graphs
+// give the same matrix. The file grows with the square of the player count.
+pub fn export_adjacency_matrix(graph: &DiGraph<String, u32>, filepath: &str) -> Result<(), AnalysisError> {
+    let node_count = graph.node_count();
+    let mut matrix = vec![vec![0u32; node_count]; node_count];
+    for edge in graph.edge_references() {
+        matrix[edge.source().index()][edge.target().index()] += edge.weight();
+    }
+
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
     let mut wtr = Writer::from_writer(BufWriter::new(file));
-    for (player, &(win_rate, draws, mean_rating_diff, game_count)) in mean_mode_metrics.iter() {
-        wtr.serialize((player.clone(), win_rate, draws, mean_rating_diff, game_count))?;
+    wtr.write_record(std::iter::once("").chain(graph.node_weights().map(String::as_str)))?;
+    for (node, row) in graph.node_indices().zip(&matrix) {
+        let mut record = vec![graph[node].clone()];
+        record.extend(row.iter().map(|count| count.to_string()));
+        wtr.write_record(&record)?;
     }
     wtr.flush()?;
     Ok(())
 }
 
-pub fn calculate_mean_mode(games: &[Game]) -> HashMap<String, (f64, f64, f64, u32)> {
-    let mut white_metrics = HashMap::new();
-    let mut black_metrics = HashMap::new();
-
+// Maps each node index to its player and how many of `games` they played,
+// so index-keyed data can be joined back to names. `normalization` must be
+// the one the graph was built with, so every spelling of a player counts
+// towards their node.
+pub fn export_roster(graph: &DiGraph<String, u32>, games: &[Game], normalization: NameNormalization, filepath: &str) -> Result<(), AnalysisError> {
+    let mut games_played: HashMap<String, u32> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
     for game in games {
-        let white_entry = white_metrics.entry(game.white.clone()).or_insert((0.0, 0.0, 0.0, 0));
-        let black_entry = black_metrics.entry(game.black.clone()).or_insert((0.0, 0.0, 0.0, 0));
+        *games_played.entry(names.display_name(&game.white)).or_insert(0) += 1;
+        *games_played.entry(names.display_name(&game.black)).or_insert(0) += 1;
+    }
 
-        match game.result.as_str() {
-            "1-0" => {
-                white_entry.0 += 1.0;
-            }
-            "0-1" => {
-                black_entry.0 += 1.0;
-            }
-            "1/2-1/2" => {
-                white_entry.1 += 0.5;
-                black_entry.1 += 0.5;
-            }
-            _ => {}
-        }
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    wtr.write_record(["node_index", "player", "games_played"])?;
+    for node in graph.node_indices() {
+        let player = graph[node].as_str();
+        wtr.serialize((node.index(), player, games_played.get(player).copied().unwrap_or(0)))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// GraphML for tools like Gephi that don't read DOT. Each player is a node with
+// a `name` attribute and each edge keeps its game count as `weight`.
+pub fn export_graph_graphml(graph: &DiGraph<String, u32>, filepath: &str) -> Result<(), AnalysisError> {
+    let mut file = BufWriter::new(File::create(filepath)?);
+    writeln!(file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(file, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(file, r#"  <key id="name" for="node" attr.name="name" attr.type="string"/>"#)?;
+    writeln!(file, r#"  <key id="weight" for="edge" attr.name="weight" attr.type="int"/>"#)?;
+    writeln!(file, r#"  <graph id="players" edgedefault="directed">"#)?;
+    for node in graph.node_indices() {
+        writeln!(file, r#"    <node id="n{}"><data key="name">{}</data></node>"#, node.index(), escape_xml(&graph[node]))?;
+    }
+    for edge in graph.edge_references() {
+        writeln!(
+            file,
+            r#"    <edge id="e{}" source="n{}" target="n{}"><data key="weight">{}</data></edge>"#,
+            edge.id().index(),
+            edge.source().index(),
+            edge.target().index(),
+            edge.weight()
+        )?;
+    }
+    writeln!(file, "  </graph>")?;
+    writeln!(file, "</graphml>")?;
+    file.flush()?;
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// Exports are sorted so repeated runs produce identical files: performance by
+// win rate, then games played, both descending, then player name. `top_n`
+// keeps only that many leading entries; `None` keeps them all.
+fn sorted_performance(performance: &HashMap<String, PlayerPerformance>, top_n: Option<usize>) -> Vec<(&String, &PlayerPerformance)> {
+    let mut sorted: Vec<(&String, &PlayerPerformance)> = performance.iter().collect();
+    sorted.sort_by(|(player_a, a), (player_b, b)| {
+        b.win_rate
+            .total_cmp(&a.win_rate)
+            .then_with(|| b.games_played.cmp(&a.games_played))
+            .then_with(|| player_a.cmp(player_b))
+    });
+    if let Some(top_n) = top_n {
+        sorted.truncate(top_n);
+    }
+    sorted
+}
+
+// Percentile rank of each value within all of them: its 1-based position in
+// ascending order over the count, times 100, so the highest value is at 100.
+// Tied values share the average of the positions they span.
+pub fn percentile_ranks<'a>(values: impl IntoIterator<Item = (&'a str, f64)>) -> HashMap<&'a str, f64> {
+    let mut sorted: Vec<(&str, f64)> = values.into_iter().collect();
+    sorted.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    let count = sorted.len() as f64;
+
+    let mut ranks = HashMap::new();
+    let mut start = 0;
+    while start < sorted.len() {
+        let end = start + sorted[start..].iter().take_while(|(_, value)| *value == sorted[start].1).count();
+        // Positions start + 1 through end, averaged.
+        let average_rank = (start + 1 + end) as f64 / 2.0;
+        for &(key, _) in &sorted[start..end] {
+            ranks.insert(key, average_rank / count * 100.0);
+        }
+        start = end;
+    }
+    ranks
+}
+
+// Scores descending, ties broken by player name.
+fn sorted_scores<'a>(scores: &HashMap<NodeIndex, f64>, graph: &'a DiGraph<String, u32>, top_n: Option<usize>) -> Vec<(&'a String, f64)> {
+    let mut sorted: Vec<(&String, f64)> = scores.iter().map(|(node, &score)| (&graph[*node], score)).collect();
+    sorted.sort_by(|(player_a, a), (player_b, b)| b.total_cmp(a).then_with(|| player_a.cmp(player_b)));
+    if let Some(top_n) = top_n {
+        sorted.truncate(top_n);
+    }
+    sorted
+}
+
+// Percentiles are taken over the whole pool, so they don't change with `top_n`.
+pub fn export_performance(performance: &HashMap<String, PlayerPerformance>, filepath: &str, top_n: Option<usize>) -> Result<(), AnalysisError> {
+    let win_rate_pctiles = percentile_ranks(performance.iter().map(|(player, stats)| (player.as_str(), stats.win_rate)));
+    let rating_change_pctiles = percentile_ranks(performance.iter().map(|(player, stats)| (player.as_str(), stats.total_rating_change as f64)));
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    for (rank, (player, stats)) in sorted_performance(performance, top_n).into_iter().enumerate() {
+        wtr.serialize((
+            rank + 1,
+            player,
+            stats.games_played,
+            stats.games_won,
+            stats.games_lost,
+            stats.games_drawn,
+            stats.total_rating_change,
+            stats.win_rate,
+            win_rate_pctiles[player.as_str()],
+            rating_change_pctiles[player.as_str()],
+        ))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn export_centrality_data_json(scores: &HashMap<NodeIndex, f64>, graph: &DiGraph<String, u32>, filepath: &str, top_n: Option<usize>) -> Result<(), AnalysisError> {
+    let records: Vec<PlayerScore> = sorted_scores(scores, graph, top_n)
+        .into_iter()
+        .map(|(player, score)| PlayerScore { player, score })
+        .collect();
+    let file = File::create(filepath)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &records)?;
+    Ok(())
+}
+
+pub fn export_performance_json(performance: &HashMap<String, PlayerPerformance>, filepath: &str, top_n: Option<usize>) -> Result<(), AnalysisError> {
+    let records: Vec<PlayerPerformanceRecord> = sorted_performance(performance, top_n)
+        .into_iter()
+        .map(|(player, stats)| PlayerPerformanceRecord { player, performance: stats })
+        .collect();
+    let file = File::create(filepath)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &records)?;
+    Ok(())
+}
+
+/// Everything that goes into `export_wide_player_table`. Name-keyed metrics
+/// are matched to the graph's nodes by player name.
+pub struct PlayerTable<'a> {
+    pub graph: &'a DiGraph<String, u32>,
+    pub pagerank_scores: &'a HashMap<NodeIndex, f64>,
+    pub betweenness_centrality: &'a HashMap<NodeIndex, f64>,
+    pub closeness_centrality: &'a HashMap<NodeIndex, f64>,
+    pub performance: &'a HashMap<String, PlayerPerformance>,
+    pub distinct_opponents: &'a HashMap<String, usize>,
+    /// From `upset_counts`, so players without upset wins are missing and
+    /// written as 0.
+    pub upsets: &'a HashMap<String, u32>,
+    /// From `elo_performance_delta`.
+    pub elo_deltas: &'a HashMap<String, f64>,
+    /// From `longest_win_streak`.
+    pub win_streaks: &'a HashMap<String, u32>,
+    /// From `calculate_strength_centrality`, keyed by node like the scores.
+    pub strength: &'a HashMap<NodeIndex, (u32, u32)>,
+}
+
+// One row per player with every per-player metric side by side, joined on the
+// player's name. Metrics a player doesn't have (e.g. no graph node) are left
+// empty rather than written as 0. Rows are sorted by name.
+pub fn export_wide_player_table(table: &PlayerTable, filepath: &str) -> Result<(), AnalysisError> {
+    let graph = table.graph;
+    let nodes: HashMap<&str, NodeIndex> = graph.node_indices().map(|node| (graph[node].as_str(), node)).collect();
+    let players: BTreeSet<&str> = nodes.keys().copied().chain(table.performance.keys().map(String::as_str)).collect();
+
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    wtr.write_record(["player", "pagerank", "betweenness", "closeness", "win_rate", "games", "mean_rating_diff", "distinct_opponents", "upsets", "elo_delta", "longest_win_streak", "strength_in", "strength_out"])?;
+    for player in players {
+        let node = nodes.get(player);
+        let score = |scores: &HashMap<NodeIndex, f64>| node.and_then(|node| scores.get(node).copied());
+        let stats = table.performance.get(player).filter(|stats| stats.games_played > 0);
+        let strength = node.and_then(|node| table.strength.get(node));
+        wtr.serialize((
+            player,
+            score(table.pagerank_scores),
+            score(table.betweenness_centrality),
+            score(table.closeness_centrality),
+            stats.map(|stats| stats.win_rate),
+            stats.map(|stats| stats.games_played),
+            stats.map(|stats| stats.total_rating_change as f64 / stats.games_played as f64),
+            table.distinct_opponents.get(player),
+            stats.map(|_| table.upsets.get(player).copied().unwrap_or(0)),
+            table.elo_deltas.get(player),
+            table.win_streaks.get(player),
+            strength.map(|&(strength_in, _)| strength_in),
+            strength.map(|&(_, strength_out)| strength_out),
+        ))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn track_player_performance_with(games: &[Game], normalization: NameNormalization) -> HashMap<String, PlayerPerformance> {
+    let mut white_performance: HashMap<String, PlayerPerformance> = HashMap::new();
+    let mut black_performance: HashMap<String, PlayerPerformance> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let (white_result, black_result) = match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) => ("1-0", "0-1"),
+            Some(GameResult::BlackWin) => ("0-1", "1-0"),
+            Some(GameResult::Draw) => ("1/2-1/2", "1/2-1/2"),
+            None => continue,
+        };
+
+        let white_entry = white_performance.entry(names.display_name(&game.white)).or_default();
+        let black_entry = black_performance.entry(names.display_name(&game.black)).or_default();
+
+        white_entry.update(white_result, game.white_rating_diff.unwrap_or(0.0));
+        black_entry.update(black_result, game.black_rating_diff.unwrap_or(0.0));
+    }
+
+    merge_performance_maps(white_performance, black_performance)
+}
+
+// How many different players each player has faced, with either color and
+// whatever the result. Unlike degree centrality this doesn't depend on how
+// the graph was built. A game against oneself doesn't count.
+pub fn distinct_opponents_with(games: &[Game], normalization: NameNormalization) -> HashMap<String, usize> {
+    let mut opponents: HashMap<String, HashSet<String>> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let white = names.display_name(&game.white);
+        let black = names.display_name(&game.black);
+        if white == black {
+            continue;
+        }
+        opponents.entry(white.clone()).or_default().insert(black.clone());
+        opponents.entry(black).or_default().insert(white);
+    }
+
+    opponents.into_iter().map(|(player, faced)| (player, faced.len())).collect()
+}
+
+// The `n` players with the most games (either color, any result), most
+// active first and ties broken by name.
+pub fn most_active_players(games: &[Game], n: usize) -> Vec<(String, u32)> {
+    let mut game_counts: HashMap<&str, u32> = HashMap::new();
+    for game in games {
+        *game_counts.entry(game.white.as_str()).or_insert(0) += 1;
+        *game_counts.entry(game.black.as_str()).or_insert(0) += 1;
+    }
+
+    let mut players: Vec<(&str, u32)> = game_counts.into_iter().collect();
+    players.sort_by(|(player_a, a), (player_b, b)| b.cmp(a).then_with(|| player_a.cmp(player_b)));
+    players.into_iter().take(n).map(|(player, count)| (player.to_string(), count)).collect()
+}
+
+// Each player's longest run of consecutive wins, with either color. Streaks
+// only mean something if `games` is in the order they were played (by date,
+// or the input's row order when that's chronological), since games are
+// walked as given. A loss or draw ends the run; games whose result can't be
+// read are skipped without breaking it. Players who never won get 0.
+pub fn longest_win_streak(games: &[Game], normalization: NameNormalization) -> HashMap<String, u32> {
+    let mut current: HashMap<String, u32> = HashMap::new();
+    let mut longest: HashMap<String, u32> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let (white_won, black_won) = match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) => (true, false),
+            Some(GameResult::BlackWin) => (false, true),
+            Some(GameResult::Draw) => (false, false),
+            None => continue,
+        };
+        for (player, won) in [(names.display_name(&game.white), white_won), (names.display_name(&game.black), black_won)] {
+            let streak = current.entry(player.clone()).or_insert(0);
+            *streak = if won { *streak + 1 } else { 0 };
+            let best = longest.entry(player).or_insert(0);
+            *best = (*best).max(*streak);
+        }
+    }
+
+    longest
+}
+
+// Players found in both maps get their records summed rather than one
+// replacing the other.
+pub fn merge_performance_maps(
+    a: HashMap<String, PlayerPerformance>,
+    b: HashMap<String, PlayerPerformance>,
+) -> HashMap<String, PlayerPerformance> {
+    let mut merged = a;
+    for (player, performance) in b {
+        match merged.entry(player) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(&performance),
+            Entry::Vacant(entry) => {
+                entry.insert(performance);
+            }
+        }
+    }
+    merged
+}
+
+#[derive(Default, Debug, Serialize)]
+pub struct ColorSplitPerformance {
+    pub white: PlayerPerformance,
+    pub black: PlayerPerformance,
+}
+
+// Same counting as track_player_performance, but each game lands in the half
+// for the color the player had. A player who only ever had one color keeps
+// an all-zero record for the other.
+pub fn track_color_split_performance(games: &[Game], normalization: NameNormalization) -> HashMap<String, ColorSplitPerformance> {
+    let mut performance: HashMap<String, ColorSplitPerformance> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let (white_result, black_result) = match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) => ("1-0", "0-1"),
+            Some(GameResult::BlackWin) => ("0-1", "1-0"),
+            Some(GameResult::Draw) => ("1/2-1/2", "1/2-1/2"),
+            None => continue,
+        };
+
+        performance
+            .entry(names.display_name(&game.white))
+            .or_default()
+            .white
+            .update(white_result, game.white_rating_diff.unwrap_or(0.0));
+        performance
+            .entry(names.display_name(&game.black))
+            .or_default()
+            .black
+            .update(black_result, game.black_rating_diff.unwrap_or(0.0));
+    }
+
+    performance
+}
+
+pub fn export_color_split_performance(performance: &HashMap<String, ColorSplitPerformance>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut players: Vec<&String> = performance.keys().collect();
+    players.sort();
+    for player in players {
+        let split = &performance[player];
+        wtr.serialize((
+            player,
+            split.white.games_played,
+            split.white.win_rate,
+            split.black.games_played,
+            split.black.win_rate,
+        ))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Players whose every game was drawn or had a result that couldn't be read,
+// sorted by name. Lots of them usually means the results didn't import.
+pub fn players_without_decisive_games(games: &[Game]) -> Vec<String> {
+    let mut decisive = HashSet::new();
+    let mut players = HashSet::new();
+    for game in games {
+        players.insert(game.white.as_str());
+        players.insert(game.black.as_str());
+        if matches!(GameResult::from_game(game), Some(GameResult::WhiteWin | GameResult::BlackWin)) {
+            decisive.insert(game.white.as_str());
+            decisive.insert(game.black.as_str());
+        }
+    }
+
+    let mut without: Vec<String> = players.difference(&decisive).map(|player| player.to_string()).collect();
+    without.sort();
+    without
+}
+
+pub fn head_to_head(games: &[Game], player_a: &str, player_b: &str) -> (u32, u32, u32) {
+    let mut record = (0, 0, 0);
+
+    for game in games {
+        let a_is_white = game.white == player_a && game.black == player_b;
+        let a_is_black = game.white == player_b && game.black == player_a;
+        if !a_is_white && !a_is_black {
+            continue;
+        }
+
+        match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) if a_is_white => record.0 += 1,
+            Some(GameResult::BlackWin) if a_is_black => record.0 += 1,
+            Some(GameResult::WhiteWin) | Some(GameResult::BlackWin) => record.1 += 1,
+            Some(GameResult::Draw) => record.2 += 1,
+            None => {}
+        }
+    }
+
+    record
+}
+
+pub fn head_to_head_records(games: &[Game]) -> HashMap<(String, String), (u32, u32, u32)> {
+    let mut records: HashMap<(String, String), (u32, u32, u32)> = HashMap::new();
+
+    for game in games {
+        let white_first = game.white <= game.black;
+        let key = if white_first {
+            (game.white.clone(), game.black.clone())
+        } else {
+            (game.black.clone(), game.white.clone())
+        };
+        let entry = records.entry(key).or_insert((0, 0, 0));
+
+        match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) if white_first => entry.0 += 1,
+            Some(GameResult::BlackWin) if !white_first => entry.0 += 1,
+            Some(GameResult::WhiteWin) | Some(GameResult::BlackWin) => entry.1 += 1,
+            Some(GameResult::Draw) => entry.2 += 1,
+            None => {}
+        }
+    }
+
+    records
+}
+
+pub fn export_head_to_head(games: &[Game], filepath: &str) -> Result<(), AnalysisError> {
+    let records = head_to_head_records(games);
+    let mut pairs: Vec<_> = records.iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(b.0));
+
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    for ((player_a, player_b), &(a_wins, b_wins, draws)) in pairs {
+        wtr.serialize((player_a, player_b, a_wins, b_wins, draws))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn calculate_in_out_degree_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, (usize, usize)> {
+    let mut in_out_degree_centrality = HashMap::new();
+
+    for node in graph.node_indices() {
+        let in_degree = graph.neighbors_directed(node, petgraph::Direction::Incoming).count();
+        let out_degree = graph.neighbors_directed(node, petgraph::Direction::Outgoing).count();
+        in_out_degree_centrality.insert(node, (in_degree, out_degree));
+    }
+
+    in_out_degree_centrality
+}
 
-        white_entry.2 += game.white_rating_diff.unwrap_or(0.0) as f64; 
-        black_entry.2 += game.black_rating_diff.unwrap_or(0.0) as f64;
+// Like the in/out degree, but summing edge weights, so every game counts
+// rather than every distinct opponent: (games lost, games won) with draws
+// counted both ways under bidirectional draws.
+pub fn calculate_strength_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, (u32, u32)> {
+    graph
+        .node_indices()
+        .map(|node| {
+            let in_strength = graph.edges_directed(node, petgraph::Direction::Incoming).map(|edge| *edge.weight()).sum();
+            let out_strength = graph.edges_directed(node, petgraph::Direction::Outgoing).map(|edge| *edge.weight()).sum();
+            (node, (in_strength, out_strength))
+        })
+        .collect()
+}
+
+pub fn connected_components(graph: &DiGraph<String, u32>) -> Vec<Vec<String>> {
+    let mut union_find = UnionFind::new(graph.node_count());
+    for edge in graph.edge_references() {
+        union_find.union(edge.source().index(), edge.target().index());
+    }
 
-        white_entry.3 += 1; 
-        black_entry.3 += 1; 
+    let mut component_ids: HashMap<usize, usize> = HashMap::new();
+    let mut components: Vec<Vec<String>> = Vec::new();
+    for node in graph.node_indices() {
+        let root = union_find.find(node.index());
+        let component_id = *component_ids.entry(root).or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[component_id].push(graph[node].clone());
     }
 
-    let mut player_metrics = HashMap::new();
-    for (player, (wins, draws, rating_diff_sum, game_count)) in white_metrics.into_iter().chain(black_metrics.into_iter()) {
-        let win_rate = wins / game_count as f64;
-        let mean_rating_diff = rating_diff_sum / game_count as f64;
-        player_metrics.insert(player, (win_rate, draws, mean_rating_diff, game_count));
+    components
+}
+
+pub fn export_connected_components(components: &[Vec<String>], filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    for (component_id, players) in components.iter().enumerate() {
+        for player in players {
+            wtr.serialize((component_id, player))?;
+        }
     }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Counted over pairs of players rather than single edges: of the pairs joined
+// by at least one edge, the share joined both ways. Repeat games between a
+// pair are already folded into one weighted edge, so they don't skew the
+// ratio. Self-loops are ignored and a graph without edges scores 0.
+pub fn graph_reciprocity(graph: &DiGraph<String, u32>) -> f64 {
+    let edges = distinct_edges(graph);
+    let mut connected_pairs = 0;
+    let mut mutual_pairs = 0;
 
-    player_metrics
-} 
+    for &(source, target) in &edges {
+        if edges.contains(&(target, source)) {
+            // Both directions are visited, so count the pair once.
+            if source < target {
+                connected_pairs += 1;
+                mutual_pairs += 1;
+            }
+        } else {
+            connected_pairs += 1;
+        }
+    }
+
+    if connected_pairs == 0 {
+        0.0
+    } else {
+        mutual_pairs as f64 / connected_pairs as f64
+    }
+}
+
+// Share of the V*(V-1) possible directed edges that are present. Parallel
+// edges and self-loops don't count, so it stays between 0 and 1; a graph
+// with fewer than two players scores 0.
+pub fn graph_density(graph: &DiGraph<String, u32>) -> f64 {
+    let node_count = graph.node_count();
+    if node_count < 2 {
+        return 0.0;
+    }
+    distinct_edges(graph).len() as f64 / (node_count * (node_count - 1)) as f64
+}
+
+// Each (source, target) pair joined by at least one edge, so parallel edges
+// count once. Self-loops are left out.
+fn distinct_edges(graph: &DiGraph<String, u32>) -> HashSet<(NodeIndex, NodeIndex)> {
+    graph
+        .edge_references()
+        .map(|edge| (edge.source(), edge.target()))
+        .filter(|(source, target)| source != target)
+        .collect()
+}
+
+/// A chain of players from `longest_dominance_chain`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DominanceChain {
+    pub players: Vec<String>,
+    /// The graph has a cycle and the search stopped at the depth limit, so a
+    /// longer chain may exist.
+    pub depth_limited: bool,
+}
+
+/// Longest run of players where each beat the next, A beat B, B beat C, ...
+///
+/// When the winner→loser graph is acyclic the answer is exact and found in
+/// linear time by walking it in topological order. Any cycle (including the
+/// two-way edges bidirectional draws add) makes the problem NP-hard, so the
+/// search falls back to a depth-first walk from every player that stops
+/// extending a chain once it has `max_depth` edges. If it gets that far the
+/// result is only the longest chain within that depth, not necessarily the
+/// longest overall, and is marked `depth_limited`; if it never does, every
+/// chain was seen and the result is exact. Keep `max_depth` small on large
+/// graphs since the walk is exponential in it. Ties go to the chain found
+/// first, and an empty graph gives an empty chain.
+pub fn longest_dominance_chain(graph: &DiGraph<String, u32>, max_depth: usize) -> DominanceChain {
+    let (chain, depth_limited) = match toposort(graph, None) {
+        Ok(order) => (longest_path_in_dag(graph, &order), false),
+        Err(_) => {
+            let mut best = Vec::new();
+            let mut path = Vec::new();
+            let mut on_path = vec![false; graph.node_count()];
+            // A chain of max_depth edges can't be beaten, so stop at the first.
+            for start in graph.node_indices() {
+                if best.len() > max_depth {
+                    break;
+                }
+                extend_chain(graph, start, max_depth, &mut path, &mut on_path, &mut best);
+            }
+            let depth_limited = best.len() > max_depth;
+            (best, depth_limited)
+        }
+    };
+
+    DominanceChain {
+        players: chain.into_iter().map(|node| graph[node].clone()).collect(),
+        depth_limited,
+    }
+}
+
+fn longest_path_in_dag(graph: &DiGraph<String, u32>, order: &[NodeIndex]) -> Vec<NodeIndex> {
+    // Players on the longest chain starting at each node, and the next step.
+    let mut length = vec![1usize; graph.node_count()];
+    let mut next: Vec<Option<NodeIndex>> = vec![None; graph.node_count()];
+    for &node in order.iter().rev() {
+        for successor in graph.neighbors(node) {
+            if length[successor.index()] + 1 > length[node.index()] {
+                length[node.index()] = length[successor.index()] + 1;
+                next[node.index()] = Some(successor);
+            }
+        }
+    }
+
+    let mut chain = Vec::new();
+    let mut current = order.iter().copied().max_by_key(|node| (length[node.index()], Reverse(node.index())));
+    while let Some(node) = current {
+        chain.push(node);
+        current = next[node.index()];
+    }
+    chain
+}
 
+fn extend_chain(
+    graph: &DiGraph<String, u32>,
+    node: NodeIndex,
+    max_depth: usize,
+    path: &mut Vec<NodeIndex>,
+    on_path: &mut Vec<bool>,
+    best: &mut Vec<NodeIndex>,
+) {
+    path.push(node);
+    on_path[node.index()] = true;
+    if path.len() > best.len() {
+        best.clone_from(path);
+    }
+
+    if path.len() <= max_depth {
+        for successor in graph.neighbors(node) {
+            if best.len() > max_depth {
+                break;
+            }
+            if !on_path[successor.index()] {
+                extend_chain(graph, successor, max_depth, path, on_path, best);
+            }
+        }
+    }
+
+    on_path[node.index()] = false;
+    path.pop();
+}
+
+// Directed 3-cycles A → B → C → A. Each cycle is reported once, rotated to
+// start at the player added to the graph first, and the list is sorted by
+// name. With bidirectional draws a triangle of drawn games also qualifies,
+// so build the graph with `DrawPolicy::Skip` to count decisive games only.
+pub fn find_intransitive_triples(graph: &DiGraph<String, u32>) -> Vec<(String, String, String)> {
+    let mut triples = Vec::new();
+
+    for edge in graph.edge_references() {
+        let (first, second) = (edge.source(), edge.target());
+        if second <= first {
+            continue;
+        }
+        for third in graph.neighbors(second) {
+            if third > first && third != second && graph.contains_edge(third, first) {
+                triples.push((graph[first].clone(), graph[second].clone(), graph[third].clone()));
+            }
+        }
+    }
+
+    // Parallel edges would find the same cycle more than once.
+    triples.sort();
+    triples.dedup();
+    triples
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct GraphDiff {
+    pub added_players: Vec<String>,
+    pub removed_players: Vec<String>,
+    /// (source, target, old weight, new weight); a missing edge counts as 0.
+    pub changed_edges: Vec<(String, String, u32, u32)>,
+}
+
+// Parallel edges are summed, so a multigraph gives the same weights as the
+// collapsed one.
+fn edge_weights_by_name(graph: &DiGraph<String, u32>) -> HashMap<(&str, &str), u32> {
+    let mut weights = HashMap::new();
+    for edge in graph.edge_references() {
+        *weights.entry((graph[edge.source()].as_str(), graph[edge.target()].as_str())).or_insert(0) += *edge.weight();
+    }
+    weights
+}
+
+// Nodes are matched by player name, since the same player usually gets a
+// different index in graphs built from different datasets. Everything is
+// sorted by name so the diff reads the same from run to run.
+pub fn graph_diff(old: &DiGraph<String, u32>, new: &DiGraph<String, u32>) -> GraphDiff {
+    let old_players: HashSet<&str> = old.node_weights().map(String::as_str).collect();
+    let new_players: HashSet<&str> = new.node_weights().map(String::as_str).collect();
+    let mut added_players: Vec<String> = new_players.difference(&old_players).map(|player| player.to_string()).collect();
+    let mut removed_players: Vec<String> = old_players.difference(&new_players).map(|player| player.to_string()).collect();
+    added_players.sort();
+    removed_players.sort();
+
+    let old_weights = edge_weights_by_name(old);
+    let new_weights = edge_weights_by_name(new);
+    let mut changed_edges: Vec<(String, String, u32, u32)> = old_weights
+        .keys()
+        .chain(new_weights.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter_map(|&(source, target)| {
+            let old_weight = old_weights.get(&(source, target)).copied().unwrap_or(0);
+            let new_weight = new_weights.get(&(source, target)).copied().unwrap_or(0);
+            (old_weight != new_weight).then(|| (source.to_string(), target.to_string(), old_weight, new_weight))
+        })
+        .collect();
+    changed_edges.sort();
+
+    GraphDiff { added_players, removed_players, changed_edges }
+}
+
+pub fn export_graph_diff(diff: &GraphDiff, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    for player in &diff.added_players {
+        wtr.serialize(("added_player", player, None::<&str>, None::<u32>, None::<u32>))?;
+    }
+    for player in &diff.removed_players {
+        wtr.serialize(("removed_player", player, None::<&str>, None::<u32>, None::<u32>))?;
+    }
+    for (source, target, old_weight, new_weight) in &diff.changed_edges {
+        wtr.serialize(("changed_edge", source, Some(target), Some(old_weight), Some(new_weight)))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Fewest-hop chain of players from `from` to `to`, following edges in their
+// direction (so with the default graph, each player beat the next one).
+// Returns None if either player is missing or `to` can't be reached.
+pub fn shortest_path_between(graph: &DiGraph<String, u32>, from: &str, to: &str) -> Option<Vec<String>> {
+    let start = graph.node_indices().find(|&node| graph[node] == from)?;
+    let goal = graph.node_indices().find(|&node| graph[node] == to)?;
+
+    let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        if node == goal {
+            let mut path = vec![graph[goal].clone()];
+            let mut current = goal;
+            while let Some(&before) = previous.get(&current) {
+                path.push(graph[before].clone());
+                current = before;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for neighbor in graph.neighbors_directed(node, Direction::Outgoing) {
+            if visited.insert(neighbor) {
+                previous.insert(neighbor, node);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    None
+}
+
+// Edge weights count games, but shortest paths need distances, so a pair who
+// met k times is treated as 1/k apart: the more often two players met, the
+// closer they are. Weights are never zero, the max(1) is only a guard.
+pub fn game_count_distance(weight: u32) -> f64 {
+    1.0 / weight.max(1) as f64
+}
+
+// Game-count distance from `source` to every player it reaches. Pass
+// `Reversed(graph)` for the distances to `source` instead.
+pub fn weighted_distances<G>(graph: G, source: G::NodeId) -> HashMap<G::NodeId, f64>
+where
+    G: IntoEdges<EdgeWeight = u32> + Visitable,
+    G::NodeId: Eq + std::hash::Hash,
+{
+    dijkstra(graph, source, None, |edge| game_count_distance(*edge.weight()))
+}
+
+// Distances are sums of 1/k, which round differently depending on the order
+// they're added in, so paths of equal length can differ in the last bits.
+const DISTANCE_TOLERANCE: f64 = 1e-9;
+
+fn same_distance(a: f64, b: f64) -> bool {
+    (a - b).abs() <= DISTANCE_TOLERANCE * a.abs().max(b.abs())
+}
+
+#[derive(Clone, Copy)]
+struct QueuedPath {
+    distance: f64,
+    node: NodeIndex,
+}
+
+impl PartialEq for QueuedPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedPath {}
+
+impl Ord for QueuedPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for QueuedPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Brandes' algorithm with Dijkstra in place of BFS, counting endpoints and
+// normalized the same way as the unweighted betweenness above.
+fn weighted_betweenness_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
+    let mut betweenness: HashMap<NodeIndex, f64> = graph.node_indices().map(|node| (node, 0.0)).collect();
+
+    for source in graph.node_indices() {
+        let mut order = Vec::new();
+        let mut predecessors: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        let mut path_counts: HashMap<NodeIndex, f64> = HashMap::new();
+        let mut settled: HashMap<NodeIndex, f64> = HashMap::new();
+        let mut seen: HashMap<NodeIndex, f64> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+
+        path_counts.insert(source, 1.0);
+        seen.insert(source, 0.0);
+        queue.push(QueuedPath { distance: 0.0, node: source });
+
+        while let Some(QueuedPath { distance, node }) = queue.pop() {
+            if settled.contains_key(&node) {
+                continue;
+            }
+            settled.insert(node, distance);
+            order.push(node);
+
+            for edge in graph.edges(node) {
+                let target = edge.target();
+                let target_distance = distance + game_count_distance(*edge.weight());
+                if settled.contains_key(&target) {
+                    continue;
+                }
+                match seen.get(&target) {
+                    Some(&best) if same_distance(target_distance, best) => {
+                        *path_counts.entry(target).or_insert(0.0) += path_counts[&node];
+                        predecessors.entry(target).or_default().push(node);
+                    }
+                    Some(&best) if target_distance > best => {}
+                    _ => {
+                        seen.insert(target, target_distance);
+                        path_counts.insert(target, path_counts[&node]);
+                        predecessors.insert(target, vec![node]);
+                        queue.push(QueuedPath { distance: target_distance, node: target });
+                    }
+                }
+            }
+        }
+
+        *betweenness.get_mut(&source).unwrap() += (order.len() - 1) as f64;
+        let mut dependency: HashMap<NodeIndex, f64> = HashMap::new();
+        while let Some(node) = order.pop() {
+            let node_dependency = dependency.get(&node).copied().unwrap_or(0.0);
+            let coefficient = (1.0 + node_dependency) / path_counts[&node];
+            for predecessor in predecessors.get(&node).into_iter().flatten() {
+                *dependency.entry(*predecessor).or_insert(0.0) += path_counts[predecessor] * coefficient;
+            }
+            if node != source {
+                *betweenness.get_mut(&node).unwrap() += node_dependency + 1.0;
+            }
+        }
+    }
+
+    let node_count = graph.node_count();
+    if node_count >= 2 {
+        let scale = 1.0 / (node_count * (node_count - 1)) as f64;
+        for score in betweenness.values_mut() {
+            *score *= scale;
+        }
+    }
+
+    betweenness
+}
+
+// Wasserman-Faust closeness over the distances from every other player to
+// this one, matching the unweighted closeness above.
+fn weighted_closeness_centrality(graph: &DiGraph<String, u32>) -> HashMap<NodeIndex, f64> {
+    let node_count = graph.node_count();
+
+    graph
+        .node_indices()
+        .map(|node| {
+            let distances = weighted_distances(Reversed(graph), node);
+            let reachable = (distances.len() - 1) as f64;
+            let total_distance: f64 = distances.values().sum();
+            let score = if total_distance > 0.0 && node_count > 1 {
+                (reachable / total_distance) * (reachable / (node_count - 1) as f64)
+            } else {
+                0.0
+            };
+            (node, score)
+        })
+        .collect()
+}
+
+pub fn calculate_weighted_centrality(graph: &DiGraph<String, u32>) -> (HashMap<NodeIndex, f64>, HashMap<NodeIndex, f64>) {
+    (weighted_betweenness_centrality(graph), weighted_closeness_centrality(graph))
+}
+
+pub fn export_in_out_degree_centrality(
+    in_out_degree_centrality: &HashMap<NodeIndex, (usize, usize)>,
+    graph: &DiGraph<String, u32>,
+    filepath: &str,
+) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut nodes: Vec<&NodeIndex> = in_out_degree_centrality.keys().collect();
+    nodes.sort_by_key(|node| &graph[**node]);
+    for node in nodes {
+        let (in_degree, out_degree) = in_out_degree_centrality[node];
+        wtr.serialize((graph[*node].clone(), in_degree, out_degree))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn export_weighted_centrality(
+    weighted_betweenness: &HashMap<NodeIndex, f64>,
+    weighted_closeness: &HashMap<NodeIndex, f64>,
+    graph: &DiGraph<String, u32>,
+    filepath: &str,
+) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut nodes: Vec<&NodeIndex> = weighted_betweenness.keys().collect();
+    nodes.sort_by_key(|node| &graph[**node]);
+    for node in nodes {
+        let betweenness = weighted_betweenness[node];
+        let closeness = weighted_closeness[node];
+        wtr.serialize((graph[*node].clone(), betweenness, closeness))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn export_mean_mode_metrics(
+    mean_mode_metrics: &HashMap<String, (f64, f64, f64, u32)>,
+    rating_volatility: &HashMap<String, f64>,
+    most_common_opening: &HashMap<String, String>,
+    filepath: &str,
+) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut players: Vec<&String> = mean_mode_metrics.keys().collect();
+    players.sort();
+    for player in players {
+        let (win_rate, draws, mean_rating_diff, game_count) = mean_mode_metrics[player];
+        let volatility = rating_volatility.get(player).copied().unwrap_or(0.0);
+        let opening = most_common_opening.get(player).cloned().unwrap_or_default();
+        wtr.serialize((player.clone(), win_rate, draws, mean_rating_diff, game_count, volatility, opening))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Win rate, draws (half a point each), mean rating diff and game count per
+// player, over their games with either color.
+pub fn calculate_mean_mode(games: &[Game], normalization: NameNormalization) -> HashMap<String, (f64, f64, f64, u32)> {
+    let mut metrics: HashMap<String, (f64, f64, f64, u32)> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let (white_wins, black_wins, draws) = match game.result.as_str() {
+            "1-0" => (1.0, 0.0, 0.0),
+            "0-1" => (0.0, 1.0, 0.0),
+            "1/2-1/2" => (0.0, 0.0, 0.5),
+            _ => (0.0, 0.0, 0.0),
+        };
+
+        for (player, wins, rating_diff) in [(&game.white, white_wins, game.white_rating_diff), (&game.black, black_wins, game.black_rating_diff)] {
+            let entry = metrics.entry(names.display_name(player)).or_insert((0.0, 0.0, 0.0, 0));
+            entry.0 += wins;
+            entry.1 += draws;
+            entry.2 += rating_diff.unwrap_or(0.0) as f64;
+            entry.3 += 1;
+        }
+    }
+
+    metrics
+        .into_iter()
+        .map(|(player, (wins, draws, rating_diff_sum, game_count))| {
+            (player, (wins / game_count as f64, draws, rating_diff_sum / game_count as f64, game_count))
+        })
+        .collect()
+}
+
+// Each player's modal ECO code over their games with either color, the
+// alphabetically first code when several are tied. Games without an ECO
+// code are ignored, so a player who never has one is left out.
+pub fn most_common_opening(games: &[Game], normalization: NameNormalization) -> HashMap<String, String> {
+    let mut eco_counts: HashMap<String, HashMap<&str, u32>> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+    for game in games {
+        if game.eco.is_empty() {
+            continue;
+        }
+        for player in [&game.white, &game.black] {
+            *eco_counts.entry(names.display_name(player)).or_default().entry(&game.eco).or_insert(0) += 1;
+        }
+    }
+
+    eco_counts
+        .into_iter()
+        .filter_map(|(player, counts)| {
+            let (eco, _) = counts
+                .into_iter()
+                .max_by(|(eco_a, a), (eco_b, b)| a.cmp(b).then_with(|| eco_b.cmp(eco_a)))?;
+            Some((player, eco.to_string()))
+        })
+        .collect()
+}
+
+// Every rating diff a player had, as white and as black, in one list. Games
+// without a diff are left out rather than counted as zero, so a player with
+// no known diffs has no entry.
+fn rating_diffs_by_player(games: &[Game], normalization: NameNormalization) -> HashMap<String, Vec<f64>> {
+    let mut rating_diffs: HashMap<String, Vec<f64>> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        for (player, rating_diff) in [(&game.white, game.white_rating_diff), (&game.black, game.black_rating_diff)] {
+            if let Some(rating_diff) = rating_diff {
+                rating_diffs.entry(names.display_name(player)).or_default().push(rating_diff as f64);
+            }
+        }
+    }
+
+    rating_diffs
+}
+
+pub fn calculate_rating_stats(games: &[Game], normalization: NameNormalization) -> HashMap<String, PlayerRatingStats> {
+    rating_diffs_by_player(games, normalization)
+        .into_iter()
+        .map(|(player, values)| (player, PlayerRatingStats::from_values(&values)))
+        .collect()
+}
+
+// Population standard deviation of each player's per-game rating diffs, so a
+// player who always gains or loses the same amount has zero volatility.
+pub fn rating_volatility(games: &[Game], normalization: NameNormalization) -> HashMap<String, f64> {
+    rating_diffs_by_player(games, normalization)
+        .into_iter()
+        .map(|(player, values)| (player, PlayerRatingStats::from_values(&values).std_dev))
+        .collect()
+}
+
+// Decisive games where both Elos are known and differ, as (winner, whether
+// the winner was the lower-rated player).
+fn rated_decisive_results(games: &[Game]) -> impl Iterator<Item = (&str, bool)> {
+    games.iter().filter_map(|game| {
+        let (white_elo, black_elo) = (game.white_elo?, game.black_elo?);
+        if white_elo == black_elo {
+            return None;
+        }
+        match GameResult::from_game(game)? {
+            GameResult::WhiteWin => Some((game.white.as_str(), white_elo < black_elo)),
+            GameResult::BlackWin => Some((game.black.as_str(), black_elo < white_elo)),
+            GameResult::Draw => None,
+        }
+    })
+}
+
+// Share of all games that were drawn, 0 when there are none. Games with an
+// unreadable result count in the total but never as draws.
+pub fn overall_draw_rate(games: &[Game]) -> f64 {
+    if games.is_empty() {
+        return 0.0;
+    }
+    let draws = games
+        .iter()
+        .filter(|game| GameResult::from_game(game) == Some(GameResult::Draw))
+        .count();
+    draws as f64 / games.len() as f64
+}
+
+// Share of decisive games between differently rated players that the
+// lower-rated one won. Draws and games missing an Elo don't count, and 0 is
+// returned when no game qualifies.
+pub fn upset_rate(games: &[Game]) -> f64 {
+    let (upsets, total) = rated_decisive_results(games)
+        .fold((0u32, 0u32), |(upsets, total), (_, upset)| (upsets + upset as u32, total + 1));
+    if total == 0 {
+        0.0
+    } else {
+        upsets as f64 / total as f64
+    }
+}
+
+// Upsets won by each player, over the same games as `upset_rate`. Players
+// with no upset wins are left out.
+pub fn upset_counts(games: &[Game], normalization: NameNormalization) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+    for (winner, upset) in rated_decisive_results(games) {
+        if upset {
+            *counts.entry(names.display_name(winner)).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+// Game ids of decisive games where the winner lost rating and the loser
+// gained it, which means the Result and the rating diffs can't both be right.
+// Games missing either diff aren't checked.
+pub fn find_result_rating_inconsistencies(games: &[Game]) -> Vec<String> {
+    games
+        .iter()
+        .filter(|game| {
+            let (white_diff, black_diff) = match (game.white_rating_diff, game.black_rating_diff) {
+                (Some(white_diff), Some(black_diff)) => (white_diff, black_diff),
+                _ => return false,
+            };
+            match GameResult::from_game(game) {
+                Some(GameResult::WhiteWin) => white_diff < 0.0 && black_diff > 0.0,
+                Some(GameResult::BlackWin) => black_diff < 0.0 && white_diff > 0.0,
+                _ => false,
+            }
+        })
+        .map(|game| game.game_id.clone())
+        .collect()
+}
+
+// Standard Elo expectation for a player rated `rating` against `opponent`.
+pub fn expected_score(rating: u32, opponent: u32) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent as f64 - rating as f64) / 400.0))
+}
+
+// Sum over each player's games of actual score (1, 0.5 or 0) minus the Elo
+// expected score, so positive means they did better than their rating
+// predicts. Games missing either Elo or with an unreadable result are skipped.
+pub fn elo_performance_delta(games: &[Game], normalization: NameNormalization) -> HashMap<String, f64> {
+    let mut deltas: HashMap<String, f64> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let (white_elo, black_elo) = match (game.white_elo, game.black_elo) {
+            (Some(white_elo), Some(black_elo)) => (white_elo, black_elo),
+            _ => continue,
+        };
+        let white_score = match GameResult::from_game(game) {
+            Some(GameResult::WhiteWin) => 1.0,
+            Some(GameResult::BlackWin) => 0.0,
+            Some(GameResult::Draw) => 0.5,
+            None => continue,
+        };
+
+        *deltas.entry(names.display_name(&game.white)).or_insert(0.0) += white_score - expected_score(white_elo, black_elo);
+        *deltas.entry(names.display_name(&game.black)).or_insert(0.0) += (1.0 - white_score) - expected_score(black_elo, white_elo);
+    }
+
+    deltas
+}
+
+// Pearson correlation between white and black Elo over the games where both
+// are known. Positive means players mostly meet similarly rated opponents.
+// 0 is returned when it's undefined: fewer than two such games, or either
+// side's Elo never varies.
+pub fn elo_assortativity(games: &[Game]) -> f64 {
+    let pairs: Vec<(f64, f64)> = games
+        .iter()
+        .filter_map(|game| Some((game.white_elo? as f64, game.black_elo? as f64)))
+        .collect();
+    if pairs.len() < 2 {
+        return 0.0;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_white = pairs.iter().map(|&(white, _)| white).sum::<f64>() / n;
+    let mean_black = pairs.iter().map(|&(_, black)| black).sum::<f64>() / n;
+    let (covariance, white_variance, black_variance) = pairs.iter().fold((0.0, 0.0, 0.0), |(cov, var_w, var_b), &(white, black)| {
+        let (dw, db) = (white - mean_white, black - mean_black);
+        (cov + dw * db, var_w + dw * dw, var_b + db * db)
+    });
+    if white_variance == 0.0 || black_variance == 0.0 {
+        return 0.0;
+    }
+    covariance / (white_variance.sqrt() * black_variance.sqrt())
+}
+
+// Player-appearances per Elo bucket, keyed by the bucket's lowest rating, so
+// a player who appears in ten games counts ten times. Both colors count and
+// missing Elos are skipped. A bucket size of 0 is treated as 1.
+pub fn rating_histogram(games: &[Game], bucket_size: u32) -> BTreeMap<u32, u32> {
+    let bucket_size = bucket_size.max(1);
+    let mut histogram = BTreeMap::new();
+    for elo in games.iter().flat_map(|game| [game.white_elo, game.black_elo]).flatten() {
+        *histogram.entry(elo / bucket_size * bucket_size).or_insert(0) += 1;
+    }
+    histogram
+}
+
+pub fn export_rating_histogram(histogram: &BTreeMap<u32, u32>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    wtr.write_record(["bucket_floor", "count"])?;
+    for (bucket_floor, count) in histogram {
+        wtr.serialize((bucket_floor, count))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn export_rating_stats(rating_stats: &HashMap<String, PlayerRatingStats>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut players: Vec<&String> = rating_stats.keys().collect();
+    players.sort();
+    for player in players {
+        let stats = &rating_stats[player];
+        wtr.serialize((player.clone(), stats.mean, stats.median, stats.std_dev, stats.count))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
@@ -0,0 +1,346 @@
+use std::error::Error;
+use std::path::PathBuf;
+use serde::Deserialize;
+use crate::data_distribution::SplitStrategy;
+use crate::analysis::{DrawPolicy, EdgeMode, GraphMode, GraphOptions, NameNormalization};
+use crate::config::Config;
+use crate::filters::FilterOptions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Csv,
+    Json,
+}
+
+pub struct Args {
+    pub inputs: Vec<PathBuf>,
+    pub out_dir: PathBuf,
+    pub subsets: usize,
+    pub format: OutputFormat,
+    pub filters: FilterOptions,
+    pub graph: GraphOptions,
+    pub parquet: bool,
+    pub top_n: Option<usize>,
+    pub reorder_columns: bool,
+    pub sqlite: Option<PathBuf>,
+    pub quiet: bool,
+    pub clean: bool,
+    pub verbose: bool,
+    pub dry_run: bool,
+    pub columns: Option<Vec<String>>,
+    pub path: Option<(String, String)>,
+    pub skip_bad_rows: bool,
+    pub wide: bool,
+    pub split: SplitStrategy,
+    pub shuffle: bool,
+    pub active: Option<usize>,
+    pub mmap: bool,
+    pub diff: Option<(PathBuf, PathBuf)>,
+}
+
+impl Args {
+    pub fn defaults() -> Result<Args, Box<dyn Error>> {
+        let current_dir = std::env::current_dir()?;
+        Ok(Args {
+            inputs: vec![current_dir.join("game1.csv"), current_dir.join("game2.csv")],
+            out_dir: current_dir,
+            subsets: 5,
+            format: OutputFormat::Csv,
+            filters: FilterOptions::default(),
+            graph: GraphOptions::default(),
+            parquet: false,
+            top_n: None,
+            reorder_columns: false,
+            sqlite: None,
+            quiet: false,
+            clean: false,
+            verbose: false,
+            dry_run: false,
+            columns: None,
+            path: None,
+            skip_bad_rows: false,
+            wide: false,
+            split: SplitStrategy::default(),
+            shuffle: false,
+            active: None,
+            mmap: false,
+            diff: None,
+        })
+    }
+
+    pub fn subset_files(&self) -> Vec<PathBuf> {
+        (1..=self.subsets)
+            .map(|i| self.out_dir.join(format!("subset_data_{}.csv", i)))
+            .collect()
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, flag: &str) -> Result<T, Box<dyn Error>> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", flag, value).into())
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, Box<dyn Error>> {
+    args.next().ok_or_else(|| format!("missing value for {}", flag).into())
+}
+
+// A --config file is applied before any other flag, wherever it appears, so
+// the flags always override it.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Args, Box<dyn Error>> {
+    let mut parsed = Args::defaults()?;
+    let args: Vec<String> = args.into_iter().collect();
+    if let Some(position) = args.iter().position(|arg| arg == "--config") {
+        let path = args.get(position + 1).ok_or("missing value for --config")?;
+        Config::load(path.as_ref())?.apply(&mut parsed)?;
+    }
+    let mut inputs = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                next_value(&mut args, "--config")?;
+            }
+            "--input" => inputs.push(PathBuf::from(next_value(&mut args, "--input")?)),
+            "--out-dir" => parsed.out_dir = PathBuf::from(next_value(&mut args, "--out-dir")?),
+            "--subsets" => {
+                parsed.subsets = parse_number(&next_value(&mut args, "--subsets")?, "--subsets")?;
+                if parsed.subsets == 0 {
+                    return Err("--subsets must be at least 1".into());
+                }
+            }
+            "--format" => {
+                parsed.format = match next_value(&mut args, "--format")?.as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    other => return Err(format!("invalid value for --format: {}", other).into()),
+                };
+            }
+            "--min-elo" => parsed.filters.min_elo = Some(parse_number(&next_value(&mut args, "--min-elo")?, "--min-elo")?),
+            "--max-elo" => parsed.filters.max_elo = Some(parse_number(&next_value(&mut args, "--max-elo")?, "--max-elo")?),
+            "--graph-mode" => {
+                parsed.graph.mode = match next_value(&mut args, "--graph-mode")?.as_str() {
+                    "directed" => GraphMode::Directed,
+                    "undirected" => GraphMode::Undirected,
+                    other => return Err(format!("invalid value for --graph-mode: {}", other).into()),
+                };
+            }
+            "--draws" => {
+                parsed.graph.draws = match next_value(&mut args, "--draws")?.as_str() {
+                    "skip" => DrawPolicy::Skip,
+                    "bidirectional" => DrawPolicy::Bidirectional,
+                    other => return Err(format!("invalid value for --draws: {}", other).into()),
+                };
+            }
+            "--edges" => {
+                parsed.graph.edges = match next_value(&mut args, "--edges")?.as_str() {
+                    "multi" => EdgeMode::Multi,
+                    "collapsed" => EdgeMode::Collapsed,
+                    other => return Err(format!("invalid value for --edges: {}", other).into()),
+                };
+            }
+            "--betweenness-samples" => {
+                let samples = parse_number(&next_value(&mut args, "--betweenness-samples")?, "--betweenness-samples")?;
+                if samples == 0 {
+                    return Err("--betweenness-samples must be at least 1".into());
+                }
+                parsed.graph.betweenness_samples = Some(samples);
+            }
+            "--recency-half-life" => {
+                let half_life = parse_number(&next_value(&mut args, "--recency-half-life")?, "--recency-half-life")?;
+                if half_life == 0 {
+                    return Err("--recency-half-life must be at least 1".into());
+                }
+                parsed.graph.recency_half_life = Some(half_life);
+            }
+            "--seed" => parsed.graph.seed = Some(parse_number(&next_value(&mut args, "--seed")?, "--seed")?),
+            "--split" => {
+                parsed.split = match next_value(&mut args, "--split")?.as_str() {
+                    "contiguous" => SplitStrategy::Contiguous,
+                    "round-robin" => SplitStrategy::RoundRobin,
+                    other => return Err(format!("invalid value for --split: {}", other).into()),
+                };
+            }
+            "--from" => parsed.filters.from = Some(parse_number(&next_value(&mut args, "--from")?, "--from")?),
+            "--to" => parsed.filters.to = Some(parse_number(&next_value(&mut args, "--to")?, "--to")?),
+            "--parquet" => parsed.parquet = true,
+            "--normalize-names" => parsed.graph.names = NameNormalization::TrimAndCaseFold,
+            "--trim-names" => parsed.graph.names = NameNormalization::Trim,
+            "--reorder-columns" => parsed.reorder_columns = true,
+            "--sqlite" => parsed.sqlite = Some(PathBuf::from(next_value(&mut args, "--sqlite")?)),
+            "--quiet" => parsed.quiet = true,
+            "--clean" => parsed.clean = true,
+            "--verbose" => parsed.verbose = true,
+            "--dry-run" => parsed.dry_run = true,
+            "--skip-bad-rows" => parsed.skip_bad_rows = true,
+            "--wide" => parsed.wide = true,
+            "--shuffle" => parsed.shuffle = true,
+            "--mmap" => parsed.mmap = true,
+            "--exclude-tos" => parsed.filters.exclude_tos = true,
+            "--decisive-only" => parsed.filters.decisive_only = true,
+            "--columns" => {
+                let value = next_value(&mut args, "--columns")?;
+                let columns: Vec<String> = value.split(',').map(|column| column.trim().to_string()).collect();
+                if columns.iter().any(|column| column.is_empty()) {
+                    return Err(format!("invalid value for --columns: {}", value).into());
+                }
+                parsed.columns = Some(columns);
+            }
+            "path" => {
+                let from = next_value(&mut args, "path")?;
+                let to = next_value(&mut args, "path")?;
+                parsed.path = Some((from, to));
+            }
+            "active" => {
+                let flag = next_value(&mut args, "active")?;
+                if flag != "--top" {
+                    return Err(format!("expected --top after active, found {}", flag).into());
+                }
+                parsed.active = Some(parse_number(&next_value(&mut args, "--top")?, "--top")?);
+            }
+            "--diff" => {
+                let old = next_value(&mut args, "--diff")?;
+                let new = next_value(&mut args, "--diff")?;
+                parsed.diff = Some((PathBuf::from(old), PathBuf::from(new)));
+            }
+            "--top-n" => parsed.top_n = Some(parse_number(&next_value(&mut args, "--top-n")?, "--top-n")?),
+            other => return Err(format!("unknown argument: {}", other).into()),
+        }
+    }
+
+    if !inputs.is_empty() {
+        parsed.inputs = inputs;
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args_defaults() {
+        let args = parse_args(Vec::new()).unwrap();
+        assert_eq!(args.inputs.len(), 2);
+        assert!(args.inputs[0].ends_with("game1.csv"));
+        assert_eq!(args.subsets, 5);
+        assert_eq!(args.subset_files().len(), 5);
+        assert_eq!(args.format, OutputFormat::Csv);
+        assert_eq!(args.graph.mode, GraphMode::Directed);
+        assert_eq!(args.graph.edges, EdgeMode::Collapsed);
+        assert_eq!(args.graph.seed, None);
+        assert_eq!(args.graph.recency_half_life, None);
+        assert!(!args.parquet);
+        assert_eq!(args.top_n, None);
+        assert_eq!(args.columns, None);
+        assert_eq!(args.path, None);
+        assert_eq!(args.active, None);
+        assert_eq!(args.diff, None);
+        assert!(!args.skip_bad_rows);
+        assert!(!args.wide);
+        assert_eq!(args.split, SplitStrategy::Contiguous);
+        assert!(!args.shuffle);
+        assert!(!args.mmap);
+        assert!(!args.filters.exclude_tos);
+        assert!(!args.filters.decisive_only);
+    }
+
+    #[test]
+    fn test_parse_args_overrides() {
+        let args = parse_args(to_args(&[
+            "--input", "a.csv", "--input", "b.csv", "--out-dir", "results", "--subsets", "3", "--split", "round-robin", "--shuffle", "--mmap",
+            "--format", "json", "--min-elo", "2000", "--max-elo", "2400", "--exclude-tos", "--decisive-only",
+            "--graph-mode", "undirected", "--draws", "skip", "--edges", "multi", "--betweenness-samples", "200", "--recency-half-life", "50", "--seed", "42", "--parquet", "--top-n", "10", "--normalize-names", "--reorder-columns", "--sqlite", "metrics.db", "--quiet", "--clean", "--verbose", "--dry-run", "--skip-bad-rows", "--wide",
+            "--columns", "White, Black,Result",
+            "--from", "2016-06-01", "--to", "2016-06-30", "path", "Alice", "Bob", "active", "--top", "5", "--diff", "may.csv", "june.csv",
+        ]))
+        .unwrap();
+        assert_eq!(args.inputs, vec![PathBuf::from("a.csv"), PathBuf::from("b.csv")]);
+        assert_eq!(args.out_dir, PathBuf::from("results"));
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(args.filters.min_elo, Some(2000));
+        assert_eq!(args.filters.max_elo, Some(2400));
+        assert!(args.filters.exclude_tos);
+        assert!(args.filters.decisive_only);
+        assert_eq!(args.graph.mode, GraphMode::Undirected);
+        assert_eq!(args.graph.draws, DrawPolicy::Skip);
+        assert_eq!(args.graph.edges, EdgeMode::Multi);
+        assert_eq!(args.graph.betweenness_samples, Some(200));
+        assert_eq!(args.graph.recency_half_life, Some(50));
+        assert_eq!(args.graph.seed, Some(42));
+        assert!(args.parquet);
+        assert_eq!(args.top_n, Some(10));
+        assert_eq!(args.graph.names, NameNormalization::TrimAndCaseFold);
+        assert_eq!(parse_args(to_args(&["--trim-names"])).unwrap().graph.names, NameNormalization::Trim);
+        assert!(args.reorder_columns);
+        assert_eq!(args.sqlite, Some(PathBuf::from("metrics.db")));
+        assert!(args.quiet);
+        assert!(args.clean);
+        assert!(args.verbose);
+        assert!(args.dry_run);
+        assert!(args.skip_bad_rows);
+        assert!(args.wide);
+        assert_eq!(args.split, SplitStrategy::RoundRobin);
+        assert!(args.shuffle);
+        assert!(args.mmap);
+        assert_eq!(args.columns, Some(vec!["White".to_string(), "Black".to_string(), "Result".to_string()]));
+        assert_eq!(args.path, Some(("Alice".to_string(), "Bob".to_string())));
+        assert_eq!(args.active, Some(5));
+        assert_eq!(args.diff, Some((PathBuf::from("may.csv"), PathBuf::from("june.csv"))));
+        assert_eq!(args.filters.from, chrono::NaiveDate::from_ymd_opt(2016, 6, 1));
+        assert_eq!(args.filters.to, chrono::NaiveDate::from_ymd_opt(2016, 6, 30));
+        assert_eq!(args.subset_files(), vec![
+            PathBuf::from("results/subset_data_1.csv"),
+            PathBuf::from("results/subset_data_2.csv"),
+            PathBuf::from("results/subset_data_3.csv"),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_args_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.toml");
+        std::fs::write(&config, "inputs = [\"a.csv\"]\nsubsets = 3\nformat = \"json\"\n\n[filters]\nmin_elo = 1500\n").unwrap();
+        let config = config.to_str().unwrap();
+
+        let args = parse_args(to_args(&["--config", config])).unwrap();
+        assert_eq!(args.inputs, vec![PathBuf::from("a.csv")]);
+        assert_eq!(args.subsets, 3);
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(args.filters.min_elo, Some(1500));
+
+        let args = parse_args(to_args(&["--subsets", "2", "--config", config, "--input", "b.csv"])).unwrap();
+        assert_eq!(args.inputs, vec![PathBuf::from("b.csv")]);
+        assert_eq!(args.subsets, 2);
+        assert_eq!(args.format, OutputFormat::Json);
+
+        assert!(parse_args(to_args(&["--config", dir.path().join("missing.toml").to_str().unwrap()])).is_err());
+        assert!(parse_args(to_args(&["--config"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_rejects_bad_values() {
+        assert!(parse_args(to_args(&["--subsets", "0"])).is_err());
+        assert!(parse_args(to_args(&["--subsets", "many"])).is_err());
+        assert!(parse_args(to_args(&["--input"])).is_err());
+        assert!(parse_args(to_args(&["--format", "xml"])).is_err());
+        assert!(parse_args(to_args(&["--edges", "parallel"])).is_err());
+        assert!(parse_args(to_args(&["--split", "random"])).is_err());
+        assert!(parse_args(to_args(&["--betweenness-samples", "0"])).is_err());
+        assert!(parse_args(to_args(&["--recency-half-life", "0"])).is_err());
+        assert!(parse_args(to_args(&["--seed", "-1"])).is_err());
+        assert!(parse_args(to_args(&["--min-elo", "-5"])).is_err());
+        assert!(parse_args(to_args(&["--bogus"])).is_err());
+        assert!(parse_args(to_args(&["path", "Alice"])).is_err());
+        assert!(parse_args(to_args(&["active", "5"])).is_err());
+        assert!(parse_args(to_args(&["--diff", "may.csv"])).is_err());
+        assert!(parse_args(to_args(&["active", "--top", "many"])).is_err());
+    }
+}
@@ -1,50 +1,172 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::Read;
+use std::path::Path;
+use csv::{ReaderBuilder, StringRecord};
+use crate::input::open_input;
 
-pub fn print_column_info(subset_files: &[&str]) -> Result<(), Box<dyn Error>> {
-    for subset_file in subset_files {
-        let file = File::open(subset_file)?;
-        let mut reader = BufReader::new(file);
-        let mut content = String::new();
-        reader.read_to_string(&mut content)?;
-
-        let lines: Vec<&str> = content.lines().collect();
-
-        if let Some(header) = lines.first() {
-            let columns: Vec<String> = header.split(',').map(|col| col.to_string()).collect();
-            println!("Column information for {}:", subset_file);
-
-            for (col_index, column) in columns.iter().enumerate() {
-                println!("Column: {}", column);
-                let mut data_type = "Unknown".to_string();
-                let mut has_null = false;
-
-                for line in lines.iter().skip(1) {
-                    let fields: Vec<&str> = line.split(',').collect();
-
-                    if let Some(value) = fields.get(col_index) {
-                        if value.trim().is_empty() {
-                            has_null = true;
-                        }
-
-                        if let Ok(_) = value.parse::<i64>() {
-                            data_type = "Integer".to_string();
-                        } else if let Ok(_) = value.parse::<f64>() {
-                            data_type = "Float".to_string();
-                        } else {
-                            data_type = "String".to_string();
-                        }
-                    }
-                }
-
-                println!("Data Type: {}", data_type);
-                println!("Contains Null: {}", has_null);
-                println!();
+// Widens Integer -> Float -> String so the reported type fits every value in
+// the column: this is the type so far after one more value. Empty cells are
+// skipped, so a column with no values stays Unknown.
+fn widen_column_type(data_type: &'static str, value: &str) -> &'static str {
+    let value = value.trim();
+    if value.is_empty() {
+        return data_type;
+    }
+    let value_type = if value.parse::<i64>().is_ok() {
+        "Integer"
+    } else if value.parse::<f64>().is_ok() {
+        "Float"
+    } else {
+        "String"
+    };
+
+    match (data_type, value_type) {
+        ("String", _) | (_, "String") => "String",
+        ("Float", _) | (_, "Float") => "Float",
+        _ => "Integer",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnReport {
+    pub name: String,
+    pub data_type: &'static str,
+    pub null_count: usize,
+    pub total_rows: usize,
+}
+
+impl ColumnReport {
+    pub fn null_percentage(&self) -> f64 {
+        if self.total_rows == 0 {
+            0.0
+        } else {
+            self.null_count as f64 / self.total_rows as f64 * 100.0
+        }
+    }
+}
+
+// A cell is null when it is blank or when the row is too short to have it.
+// Rows are read one at a time with the csv reader, so quoted fields with
+// commas in them stay whole and only the running counts are kept in memory.
+fn column_reports<R: Read>(reader: R) -> Result<Vec<ColumnReport>, csv::Error> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+    let header = reader.headers()?.clone();
+    let mut data_types = vec!["Unknown"; header.len()];
+    let mut null_counts = vec![0; header.len()];
+    let mut total_rows = 0;
+
+    let mut record = StringRecord::new();
+    while reader.read_record(&mut record)? {
+        total_rows += 1;
+        for (col_index, data_type) in data_types.iter_mut().enumerate() {
+            match record.get(col_index).filter(|value| !value.trim().is_empty()) {
+                Some(value) => *data_type = widen_column_type(data_type, value),
+                None => null_counts[col_index] += 1,
             }
         }
     }
 
+    Ok(header
+        .iter()
+        .zip(data_types)
+        .zip(null_counts)
+        .map(|((column, data_type), null_count)| ColumnReport {
+            name: column.to_string(),
+            data_type,
+            null_count,
+            total_rows,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileColumnReport {
+    pub file: String,
+    pub columns: Vec<ColumnReport>,
+}
+
+pub fn analyze_columns(subset_files: &[&str]) -> Result<Vec<FileColumnReport>, Box<dyn Error>> {
+    let mut reports = Vec::new();
+
+    for subset_file in subset_files {
+        reports.push(FileColumnReport {
+            file: subset_file.to_string(),
+            columns: column_reports(open_input(Path::new(subset_file))?)?,
+        });
+    }
+
+    Ok(reports)
+}
+
+pub fn print_column_info(subset_files: &[&str]) -> Result<(), Box<dyn Error>> {
+    for report in analyze_columns(subset_files)? {
+        if report.columns.is_empty() {
+            continue;
+        }
+
+        println!("Column information for {}:", report.file);
+        for column in &report.columns {
+            println!("Column: {}", column.name);
+            println!("Data Type: {}", column.data_type);
+            println!("Null Count: {} ({:.2}%)", column.null_count, column.null_percentage());
+            println!();
+        }
+    }
+
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_infer_column_type() {
+        let infer_column_type = |values: &[&str]| values.iter().fold("Unknown", |data_type, value| widen_column_type(data_type, value));
+        assert_eq!(infer_column_type(&["1", "2", "x"]), "String");
+        assert_eq!(infer_column_type(&["x", "1", "2"]), "String");
+        assert_eq!(infer_column_type(&["1", "2.5", "3"]), "Float");
+        assert_eq!(infer_column_type(&["1", "", "3"]), "Integer");
+        assert_eq!(infer_column_type(&["", " "]), "Unknown");
+    }
+
+    #[test]
+    fn test_column_reports_null_counts() {
+        let reports = column_reports("GameID,WhiteElo,Opening\n1,1500,Sicilian\n2,,Sicilian\n3, \n4,1600,French\n".as_bytes()).unwrap();
+
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0], ColumnReport { name: "GameID".to_string(), data_type: "Integer", null_count: 0, total_rows: 4 });
+        assert_eq!(reports[1].null_count, 2);
+        assert_eq!(reports[1].data_type, "Integer");
+        assert_eq!(reports[1].null_percentage(), 50.0);
+        assert_eq!(reports[2].null_count, 1);
+        assert_eq!(reports[2].data_type, "String");
+    }
+
+    #[test]
+    fn test_column_reports_quoted_fields() {
+        let reports = column_reports("GameID,Opening,WhiteElo\n1,\"Ruy Lopez, Morphy Defense\",1500\n2,,1600\n".as_bytes()).unwrap();
+
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[1], ColumnReport { name: "Opening".to_string(), data_type: "String", null_count: 1, total_rows: 2 });
+        assert_eq!(reports[2], ColumnReport { name: "WhiteElo".to_string(), data_type: "Integer", null_count: 0, total_rows: 2 });
+    }
+
+    #[test]
+    fn test_analyze_columns() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("games.csv");
+        std::fs::write(&file_path, "White,WhiteElo,WhiteRatingDiff\nPlayer1,1500,5.5\nPlayer2,,-3\n").unwrap();
+
+        let reports = analyze_columns(&[file_path.to_str().unwrap()]).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].file, file_path.to_str().unwrap());
+
+        let columns = &reports[0].columns;
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0], ColumnReport { name: "White".to_string(), data_type: "String", null_count: 0, total_rows: 2 });
+        assert_eq!(columns[1], ColumnReport { name: "WhiteElo".to_string(), data_type: "Integer", null_count: 1, total_rows: 2 });
+        assert_eq!(columns[2], ColumnReport { name: "WhiteRatingDiff".to_string(), data_type: "Float", null_count: 0, total_rows: 2 });
+    }
+}
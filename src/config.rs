@@ -0,0 +1,139 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::cli::{Args, OutputFormat};
+
+// Settings read from a TOML file with `--config`. Every key is optional and
+// only the ones present replace the defaults; flags on the command line are
+// applied afterwards, so they win over the file. Unknown keys are an error so
+// a misspelled setting doesn't get silently ignored.
+//
+//     inputs = ["game1.csv", "game2.csv"]
+//     out_dir = "results"
+//     subsets = 3
+//     format = "json"
+//     columns = ["White", "Black", "Result"]
+//
+//     [filters]
+//     min_elo = 1500
+//     from = "2016-06-01"
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub inputs: Option<Vec<PathBuf>>,
+    pub out_dir: Option<PathBuf>,
+    pub subsets: Option<usize>,
+    pub columns: Option<Vec<String>>,
+    pub format: Option<OutputFormat>,
+    pub filters: FilterConfig,
+}
+
+// Dates are written like the --from/--to flags, yyyy-mm-dd.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    pub min_elo: Option<u32>,
+    pub max_elo: Option<u32>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("can't read config {}: {}", path.display(), err))?;
+        Config::parse(&contents).map_err(|err| format!("invalid config {}: {}", path.display(), err).into())
+    }
+
+    pub fn parse(contents: &str) -> Result<Config, Box<dyn Error>> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    // Held to the same rules as the matching flags.
+    pub fn apply(&self, args: &mut Args) -> Result<(), Box<dyn Error>> {
+        if let Some(inputs) = &self.inputs {
+            args.inputs = inputs.clone();
+        }
+        if let Some(out_dir) = &self.out_dir {
+            args.out_dir = out_dir.clone();
+        }
+        if let Some(subsets) = self.subsets {
+            if subsets == 0 {
+                return Err("subsets must be at least 1".into());
+            }
+            args.subsets = subsets;
+        }
+        if let Some(columns) = &self.columns {
+            if columns.iter().any(|column| column.trim().is_empty()) {
+                return Err("columns can't contain an empty name".into());
+            }
+            args.columns = Some(columns.iter().map(|column| column.trim().to_string()).collect());
+        }
+        if let Some(format) = self.format {
+            args.format = format;
+        }
+        if let Some(min_elo) = self.filters.min_elo {
+            args.filters.min_elo = Some(min_elo);
+        }
+        if let Some(max_elo) = self.filters.max_elo {
+            args.filters.max_elo = Some(max_elo);
+        }
+        if let Some(from) = &self.filters.from {
+            args.filters.from = Some(from.parse().map_err(|_| format!("invalid from date: {}", from))?);
+        }
+        if let Some(to) = &self.filters.to {
+            args.filters.to = Some(to.parse().map_err(|_| format!("invalid to date: {}", to))?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+# Two months of blitz games
+inputs = ["june.csv", "july.csv.gz"]
+out_dir = "results"
+subsets = 3
+format = "json"
+columns = ["White", "Black", "Result"]
+
+[filters]
+min_elo = 1500
+max_elo = 2400
+from = "2016-06-01"
+to = "2016-07-31"
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(SAMPLE).unwrap();
+        assert_eq!(config.inputs, Some(vec![PathBuf::from("june.csv"), PathBuf::from("july.csv.gz")]));
+        assert_eq!(config.out_dir, Some(PathBuf::from("results")));
+        assert_eq!(config.subsets, Some(3));
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.columns, Some(vec!["White".to_string(), "Black".to_string(), "Result".to_string()]));
+        assert_eq!(config.filters.min_elo, Some(1500));
+        assert_eq!(config.filters.max_elo, Some(2400));
+
+        let mut args = Args::defaults().unwrap();
+        config.apply(&mut args).unwrap();
+        assert_eq!(args.subset_files().len(), 3);
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(args.filters.from, chrono::NaiveDate::from_ymd_opt(2016, 6, 1));
+        assert_eq!(args.filters.to, chrono::NaiveDate::from_ymd_opt(2016, 7, 31));
+    }
+
+    #[test]
+    fn test_parse_config_rejects_bad_settings() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("subset = 3").is_err());
+        assert!(Config::parse("format = \"xml\"").is_err());
+
+        let mut args = Args::defaults().unwrap();
+        assert!(Config::parse("subsets = 0").unwrap().apply(&mut args).is_err());
+        assert!(Config::parse("[filters]\nfrom = \"June\"").unwrap().apply(&mut args).is_err());
+    }
+}
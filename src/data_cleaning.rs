@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufWriter};
+use std::path::{Path, PathBuf};
+use csv::WriterBuilder;
+use crate::input::open_input;
+
+// Splits one CSV record into fields. Whitespace around a field is trimmed,
+// but only outside quotes: `  "  Ruy Lopez, Main  " ` keeps its inner comma
+// and spaces. The csv crate's own trimming can't tell the two apart, and a
+// space before the opening quote stops it from seeing the quote at all.
+fn split_fields(record: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut chars = record.chars().peekable();
+
+    loop {
+        let mut field = String::new();
+        while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+            chars.next();
+        }
+
+        let quoted = chars.peek() == Some(&'"');
+        if quoted {
+            chars.next();
+            while let Some(c) = chars.next() {
+                if c == '"' {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                field.push(c);
+            }
+        }
+
+        // For a quoted field this is whatever sits between the closing quote
+        // and the comma, which is kept apart from surrounding whitespace.
+        let mut rest = String::new();
+        let mut ended = true;
+        for c in chars.by_ref() {
+            if c == ',' {
+                ended = false;
+                break;
+            }
+            rest.push(c);
+        }
+        field.push_str(if quoted { rest.trim() } else { rest.trim_end() });
+        fields.push(field);
+
+        if ended {
+            return fields;
+        }
+    }
+}
+
+// A quoted field may span lines, so a record is only complete once its quotes
+// balance. Doubled quotes inside a field count twice and don't upset this.
+fn quotes_balanced(record: &str) -> bool {
+    record.matches('"').count().is_multiple_of(2)
+}
+
+// Trims every field, which also collapses `a , , b` into `a,,b`, and drops
+// rows that are empty once trimmed. Rows keep their own field count, so a
+// short row stays short for the later stages to deal with. Returns how many
+// rows were dropped.
+pub fn clean_data(input_file: &Path, output_file: &Path) -> Result<usize, Box<dyn Error>> {
+    let reader = open_input(input_file)?;
+    let mut writer = WriterBuilder::new()
+        .flexible(true)
+        .from_writer(BufWriter::new(File::create(output_file)?));
+    let mut dropped = 0;
+
+    let mut record = String::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !record.is_empty() {
+            record.push('\n');
+        }
+        record.push_str(line.strip_suffix('\r').unwrap_or(&line));
+        if !quotes_balanced(&record) {
+            continue;
+        }
+
+        let fields = split_fields(&record);
+        record.clear();
+        if fields.iter().all(|field| field.is_empty()) {
+            dropped += 1;
+            continue;
+        }
+        writer.write_record(&fields)?;
+    }
+    if !record.is_empty() {
+        return Err(format!("{}: unterminated quoted field at end of file", input_file.display()).into());
+    }
+
+    writer.flush()?;
+    Ok(dropped)
+}
+
+// Cleans each input into `dir`, keeping the file names, and returns the paths
+// of the cleaned copies in the same order.
+pub fn clean_files(input_files: &[PathBuf], dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    input_files
+        .iter()
+        .enumerate()
+        .map(|(index, input_file)| {
+            let name = input_file
+                .file_name()
+                .map(|name| name.to_string_lossy().trim_end_matches(".gz").to_string())
+                .unwrap_or_else(|| format!("input_{}.csv", index));
+            let cleaned_file = dir.join(format!("{}_{}", index, name));
+            let dropped = clean_data(input_file, &cleaned_file)?;
+            if dropped > 0 {
+                log::info!("Dropped {} empty row(s) from {}", dropped, input_file.display());
+            }
+            Ok(cleaned_file)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_clean_data() {
+        let dir = tempdir().unwrap();
+        let input_file = dir.path().join("raw.csv");
+        let output_file = dir.path().join("clean.csv");
+        std::fs::write(&input_file, "White,Opening,Black\n a ,, b \n  ,  ,  \nc,\"Ruy Lopez, Morphy Defense\", d \n").unwrap();
+
+        let dropped = clean_data(&input_file, &output_file).unwrap();
+
+        assert_eq!(dropped, 1);
+        assert_eq!(
+            std::fs::read_to_string(&output_file).unwrap(),
+            "White,Opening,Black\na,,b\nc,\"Ruy Lopez, Morphy Defense\",d\n"
+        );
+    }
+
+    #[test]
+    fn test_clean_data_keeps_quoted_fields() {
+        let dir = tempdir().unwrap();
+        let input_file = dir.path().join("raw.csv");
+        let output_file = dir.path().join("clean.csv");
+        std::fs::write(
+            &input_file,
+            "a , \"  Ruy Lopez, Main  \" ,b\n\"say \"\"hi\"\"\",\"two\nlines\"\n",
+        )
+        .unwrap();
+
+        clean_data(&input_file, &output_file).unwrap();
+
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_path(&output_file).unwrap();
+        let records: Vec<Vec<String>> = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect();
+        assert_eq!(records[0], vec!["a", "  Ruy Lopez, Main  ", "b"]);
+        assert_eq!(records[1], vec!["say \"hi\"", "two\nlines"]);
+    }
+
+    #[test]
+    fn test_split_fields() {
+        assert_eq!(split_fields(" a ,, b "), vec!["a", "", "b"]);
+        assert_eq!(split_fields("a,"), vec!["a", ""]);
+        assert_eq!(split_fields("\"x, y\""), vec!["x, y"]);
+        assert_eq!(split_fields("\"x\" ,"), vec!["x", ""]);
+    }
+}
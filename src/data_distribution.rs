@@ -1,71 +1,604 @@
 
 use std::error::Error;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use csv::{ReaderBuilder, StringRecord, Writer};
+use memmap2::Mmap;
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::error::AnalysisError;
+use crate::progress::Progress;
 
-pub fn distribute_data(
-    combined_data: &[String],
+pub fn parse_line(line: &str) -> Result<StringRecord, csv::Error> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(line.as_bytes());
+    let mut record = StringRecord::new();
+    reader.read_record(&mut record)?;
+    Ok(record)
+}
+
+/// How another file's header lines up with the reference header.
+#[derive(Debug, PartialEq)]
+pub enum HeaderMatch {
+    Same,
+    /// The same columns in a different order. Entry `i` is the position in
+    /// the file of the reference header's `i`th column.
+    Reordered(Vec<usize>),
+}
+
+// Files whose column sets differ can't be combined at all, so that is always
+// an error naming the missing and unexpected columns.
+pub fn match_header(reference: &str, header: &str) -> Result<HeaderMatch, Box<dyn Error>> {
+    let reference = parse_line(reference)?;
+    let header = parse_line(header)?;
+    if reference == header {
+        return Ok(HeaderMatch::Same);
+    }
+
+    let missing: Vec<&str> = reference.iter().filter(|col| !header.iter().any(|c| c == *col)).collect();
+    let unexpected: Vec<&str> = header.iter().filter(|col| !reference.iter().any(|c| c == *col)).collect();
+    if !missing.is_empty() || !unexpected.is_empty() || reference.len() != header.len() {
+        return Err(format!(
+            "missing columns: [{}], unexpected columns: [{}]",
+            missing.join(", "),
+            unexpected.join(", ")
+        )
+        .into());
+    }
+
+    let mapping = reference
+        .iter()
+        .map(|col| header.iter().position(|c| c == col).unwrap_or_default())
+        .collect();
+    Ok(HeaderMatch::Reordered(mapping))
+}
+
+// Rewrites a data row into the reference column order given by `match_header`.
+pub fn reorder_row(line: &str, mapping: &[usize]) -> Result<String, Box<dyn Error>> {
+    let row = parse_line(line)?;
+    let reordered: Vec<&str> = mapping.iter().map(|&idx| row.get(idx).unwrap_or_default()).collect();
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record(&reordered)?;
+    let bytes = writer.into_inner().map_err(|err| err.into_error())?;
+    Ok(String::from_utf8(bytes)?.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+/// Columns kept in the subsets when no `--columns` list is given. Any of them
+/// missing from the input is simply left out.
+pub const DEFAULT_COLUMNS: [&str; 22] = [
+    "GameID", "Event", "White", "WhiteElo", "WhiteRatingDiff",
+    "White_tosViolation", "White_playTime_total", "White_count_all",
+    "Black", "BlackElo", "BlackRatingDiff", "Black_tosViolation",
+    "Black_playTime_total", "Black_count_all", "Moves", "TotalMoves",
+    "ECO", "Opening", "TimeControl", "Result", "Date", "UTCDate"
+];
+
+/// Which subset file each row goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitStrategy {
+    /// The first rows fill the first file, the next rows the second and so
+    /// on, so subsets keep whatever clustering the input order has (often by
+    /// tournament or date).
+    #[default]
+    Contiguous,
+    /// Row i goes to file i % k, spreading every part of the input evenly.
+    RoundRobin,
+}
+
+impl SplitStrategy {
+    // The file for the row at `position` (0-based) out of `total_rows`.
+    // Either way the first `total_rows % files` files get one extra row.
+    fn file_index(self, position: usize, total_rows: usize, files: usize) -> usize {
+        match self {
+            SplitStrategy::Contiguous => {
+                let rows_per_file = total_rows / files;
+                let larger_rows = (total_rows % files) * (rows_per_file + 1);
+                if position < larger_rows {
+                    position / (rows_per_file + 1)
+                } else {
+                    total_rows % files + (position - larger_rows) / rows_per_file
+                }
+            }
+            SplitStrategy::RoundRobin => position % files,
+        }
+    }
+}
+
+// Rows are pulled from `rows` one at a time and written straight to their
+// subset file, so peak memory is a single row plus the writers' buffers no
+// matter how large the input is. `total_rows` is needed up front to size the
+// contiguous chunks.
+//
+// `columns` picks the subset columns; unlike the default set, every one of
+// them must be in the header. Either way they keep the header's order.
+//
+// With `skipped` given, a row that can't be parsed or doesn't have as many
+// fields as the header is left out and recorded there as (data row number,
+// reason) instead of failing the run. Without it unparseable rows are an
+// error and short rows are padded with empty fields.
+pub fn distribute_data<I>(
+    rows: I,
+    total_rows: usize,
     header: &str,
     output_files: &[&str],
-) -> Result<(), Box<dyn Error>> {
-    println!("Total combined data rows: {}", combined_data.len());
-    if combined_data.is_empty() {
-        println!("No data to write. Exiting.");
-        return Ok(());
-    }
-
-    let specific_columns = [
-        "GameID", "Event", "White", "WhiteElo", "WhiteRatingDiff",
-        "White_tosViolation", "White_playTime_total", "White_count_all",
-        "Black", "BlackElo", "BlackRatingDiff", "Black_tosViolation",
-        "Black_playTime_total", "Black_count_all", "Moves", "TotalMoves",
-        "ECO", "Opening", "TimeControl", "Result"
-    ];
-
-    let headers: Vec<&str> = header.split(',').collect();
-    let column_indices: Vec<usize> = headers
+    columns: Option<&[String]>,
+    mut skipped: Option<&mut Vec<(usize, String)>>,
+    split: SplitStrategy,
+) -> Result<Vec<usize>, Box<dyn Error>>
+where
+    I: IntoIterator<Item = Result<String, Box<dyn Error>>>,
+{
+    log::info!("Total combined data rows: {}", total_rows);
+    if total_rows == 0 {
+        log::warn!("No data to write");
+        return Ok(vec![0; output_files.len()]);
+    }
+
+    let headers = parse_line(header)?;
+    let column_indices = selected_column_indices(&headers, columns)?;
+    let mut writers = create_subset_writers(&headers, &column_indices, output_files)?;
+
+    let num_output_files = writers.len();
+    let mut rows_written = vec![0; num_output_files];
+    let mut progress = Progress::new("Distributing rows");
+    // A skipped row still uses up its place, so it doesn't shift the rows
+    // after it into other files.
+    for (position, line) in rows.into_iter().take(total_rows).enumerate() {
+        let line = line?;
+        let row_number = position + 1;
+        let row_data = match skipped.as_deref_mut() {
+            Some(skipped) => match parse_row(&line, headers.len()) {
+                Ok(row_data) => row_data,
+                Err(reason) => {
+                    log::debug!("Skipping row {}: {}", row_number, reason);
+                    skipped.push((row_number, reason));
+                    continue;
+                }
+            },
+            None => parse_line(&line)?,
+        };
+        let selected_row_data: Vec<&str> = column_indices
+            .iter()
+            .map(|&idx| row_data.get(idx).unwrap_or_default())
+            .collect();
+        let file_index = split.file_index(position, total_rows, num_output_files);
+        writers[file_index].write_record(&selected_row_data)?;
+        rows_written[file_index] += 1;
+        progress.tick();
+    }
+    for writer in &mut writers {
+        writer.flush()?;
+    }
+    progress.finish();
+
+    log::info!("Data writing complete. {} rows distributed.", progress.count());
+    print_distribution(output_files, &rows_written);
+    Ok(rows_written)
+}
+
+/// Does the same as `distribute_data` over plain (not gzipped) CSV files that
+/// all have `header` as their header, but memory-maps each file instead of
+/// reading it line by line. A row without quotes or stray carriage returns is
+/// split on commas in place and its selected fields are copied straight from
+/// the mapping, skipping the per-row parse and allocation; any other row goes
+/// through the same parser as `distribute_data`. The subsets come out byte for
+/// byte the same as `distribute_data` would write. Unparseable rows are an
+/// error and short rows are padded, as without `skipped` there.
+///
+/// The inputs must not change while they're mapped.
+pub fn distribute_data_mmap(
+    input_files: &[&str],
+    total_rows: usize,
+    header: &str,
+    output_files: &[&str],
+    columns: Option<&[String]>,
+    split: SplitStrategy,
+) -> Result<Vec<usize>, Box<dyn Error>> {
+    log::info!("Total combined data rows: {}", total_rows);
+    if total_rows == 0 {
+        log::warn!("No data to write");
+        return Ok(vec![0; output_files.len()]);
+    }
+
+    let headers = parse_line(header)?;
+    let column_indices = selected_column_indices(&headers, columns)?;
+    let mut writers = create_subset_writers(&headers, &column_indices, output_files)?;
+
+    let num_output_files = writers.len();
+    let mut rows_written = vec![0; num_output_files];
+    let mut position = 0;
+    let mut progress = Progress::new("Distributing rows");
+    for &input_file in input_files {
+        let file = File::open(input_file)?;
+        if file.metadata()?.len() == 0 {
+            continue;
+        }
+        // Safety: the mapping is only read, and the caller guarantees the file
+        // isn't modified or truncated while it's mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let mut lines = mapped_lines(&mmap);
+
+        let file_header = std::str::from_utf8(lines.next().unwrap_or_default())?;
+        if match_header(header, file_header)? != HeaderMatch::Same {
+            return Err(format!("{} has its columns in a different order, which --mmap can't reorder", input_file).into());
+        }
+
+        let mut fields: Vec<&[u8]> = Vec::new();
+        for line in lines {
+            if position == total_rows {
+                break;
+            }
+            let file_index = split.file_index(position, total_rows, num_output_files);
+            if line.contains(&b'"') || line.contains(&b'\r') {
+                let row_data = parse_line(std::str::from_utf8(line)?)?;
+                writers[file_index].write_record(column_indices.iter().map(|&idx| row_data.get(idx).unwrap_or_default()))?;
+            } else {
+                fields.clear();
+                fields.extend(line.split(|&byte| byte == b','));
+                writers[file_index].write_record(column_indices.iter().map(|&idx| fields.get(idx).copied().unwrap_or_default()))?;
+            }
+            rows_written[file_index] += 1;
+            position += 1;
+            progress.tick();
+        }
+    }
+    for writer in &mut writers {
+        writer.flush()?;
+    }
+    progress.finish();
+
+    log::info!("Data writing complete. {} rows distributed.", progress.count());
+    print_distribution(output_files, &rows_written);
+    Ok(rows_written)
+}
+
+// Splits like `BufRead::lines`: on \n, dropping a trailing \r, with no empty
+// line after a final newline.
+fn mapped_lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    data.split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+// Positions in `headers` of the subset columns, in header order.
+fn selected_column_indices(headers: &StringRecord, columns: Option<&[String]>) -> Result<Vec<usize>, Box<dyn Error>> {
+    let specific_columns: Vec<&str> = match columns {
+        Some(columns) => {
+            let missing: Vec<&str> = columns
+                .iter()
+                .map(String::as_str)
+                .filter(|column| !headers.iter().any(|header| header == *column))
+                .collect();
+            if !missing.is_empty() {
+                return Err(format!("requested columns not in the input: [{}]", missing.join(", ")).into());
+            }
+            columns.iter().map(String::as_str).collect()
+        }
+        None => DEFAULT_COLUMNS.to_vec(),
+    };
+
+    Ok(headers
         .iter()
         .enumerate()
-        .filter_map(|(idx, col)| specific_columns.contains(&col).then(|| idx))
-        .collect();
+        .filter(|(_, col)| specific_columns.contains(col))
+        .map(|(idx, _)| idx)
+        .collect())
+}
 
-    let selected_headers: String = column_indices
+// One writer per subset file, each started with the selected header columns.
+fn create_subset_writers(headers: &StringRecord, column_indices: &[usize], output_files: &[&str]) -> Result<Vec<Writer<BufWriter<File>>>, Box<dyn Error>> {
+    let selected_headers: Vec<&str> = column_indices
         .iter()
-        .map(|&idx| headers[idx])
-        .collect::<Vec<&str>>()
-        .join(",");
+        .map(|&idx| &headers[idx])
+        .collect();
 
-    let mut writers: Vec<BufWriter<File>> = output_files
+    let mut writers: Vec<Writer<BufWriter<File>>> = output_files
         .iter()
-        .map(|&file| File::create(file).map(BufWriter::new).map_err(Into::into))
+        .map(|&file| File::create(file).map(|f| Writer::from_writer(BufWriter::new(f))).map_err(Into::into))
         .collect::<Result<_, Box<dyn Error>>>()?;
 
     for writer in &mut writers {
-        writeln!(writer, "{}", selected_headers)?;
+        writer.write_record(&selected_headers)?;
     }
+    Ok(writers)
+}
 
-    let num_output_files = writers.len();
-    let num_rows_per_file = combined_data.len() / num_output_files;
-    let remaining_rows = combined_data.len() % num_output_files;
-
-    let mut row_index = 0;
-    for (file_index, writer) in writers.iter_mut().enumerate() {
-        let rows_to_write = num_rows_per_file + if file_index < remaining_rows { 1 } else { 0 };
-        for _ in 0..rows_to_write {
-            if let Some(line) = combined_data.get(row_index) {
-                let row_data: Vec<&str> = line.split(',').collect();
-                let selected_row_data: String = column_indices
-                    .iter()
-                    .map(|&idx| row_data[idx])
-                    .collect::<Vec<&str>>()
-                    .join(",");
-                writeln!(writer, "{}", selected_row_data)?;
+// Puts data rows (not the header) in a random order so the subsets don't
+// inherit the input's ordering. Unlike distributing, this needs every row in
+// memory. The same rows and seed always give the same order.
+pub fn shuffle_rows(mut rows: Vec<String>, seed: u64) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    rows.shuffle(&mut rng);
+    rows
+}
+
+fn parse_row(line: &str, expected_fields: usize) -> Result<StringRecord, String> {
+    let row = parse_line(line).map_err(|err| err.to_string())?;
+    if row.len() != expected_fields {
+        return Err(format!("expected {} fields, found {}", expected_fields, row.len()));
+    }
+    Ok(row)
+}
+
+fn print_distribution(output_files: &[&str], rows_written: &[usize]) {
+    let width = output_files.iter().map(|file| file.len()).max().unwrap_or(0).max("File".len());
+    println!("{:<width$}  Rows", "File", width = width);
+    for (file, rows) in output_files.iter().zip(rows_written) {
+        println!("{:<width$}  {}", file, rows, width = width);
+    }
+}
+
+// Parquet keeps the inferred column types, so reloading a subset skips both CSV
+// parsing and schema inference.
+pub fn write_combined_parquet(df: &DataFrame, path: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+    let mut df = df.clone();
+    ParquetWriter::new(BufWriter::new(file)).finish(&mut df)?;
+    Ok(())
+}
+
+// Reads back a file written by `write_combined_parquet`, which lets a
+// `--parquet` copy be passed as an input instead of its CSV.
+pub fn read_combined_parquet(path: &str) -> Result<DataFrame, AnalysisError> {
+    let file = File::open(path)?;
+    Ok(ParquetReader::new(file).finish()?)
+}
+
+// Writes `<subset>.parquet` next to an already distributed subset CSV.
+pub fn write_subset_parquet(csv_path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let df = CsvReader::from_path(csv_path)?
+        .infer_schema(None)
+        .has_header(true)
+        .finish()?;
+    let parquet_path = csv_path.with_extension("parquet");
+    write_combined_parquet(&df, parquet_path.to_str().unwrap_or_default())?;
+    Ok(parquet_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_distribute_data_quoted_fields() {
+        let header = "GameID,White,Black,Opening,Result";
+        let combined_data = vec![
+            Ok("1,Player1,Player2,\"Ruy Lopez, Morphy Defense\",1-0".to_string()),
+        ];
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("subset.csv");
+        distribute_data(combined_data, 1, header, &[file_path.to_str().unwrap()], None, None, SplitStrategy::Contiguous).unwrap();
+
+        let mut reader = csv::Reader::from_path(&file_path).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        let result_idx = headers.iter().position(|h| h == "Result").unwrap();
+        let opening_idx = headers.iter().position(|h| h == "Opening").unwrap();
+
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(record.len(), 5);
+        assert_eq!(&record[opening_idx], "Ruy Lopez, Morphy Defense");
+        assert_eq!(&record[result_idx], "1-0");
+    }
+
+    #[test]
+    fn test_distribute_data_row_counts() {
+        let header = "GameID,White";
+        let rows = (1..=7).map(|id| Ok(format!("{},Player{}", id, id)));
+
+        let dir = tempdir().unwrap();
+        let paths: Vec<_> = (1..=5).map(|i| dir.path().join(format!("subset_{}.csv", i))).collect();
+        let files: Vec<&str> = paths.iter().map(|path| path.to_str().unwrap()).collect();
+        let counts = distribute_data(rows, 7, header, &files, None, None, SplitStrategy::Contiguous).unwrap();
+
+        assert_eq!(counts, vec![2, 2, 1, 1, 1]);
+        let first = std::fs::read_to_string(&paths[0]).unwrap();
+        assert_eq!(first, "GameID,White\n1,Player1\n2,Player2\n");
+    }
+
+    #[test]
+    fn test_distribute_data_round_robin() {
+        let header = "GameID,White";
+        let rows = (1..=5).map(|id| Ok(format!("{},Player{}", id, id)));
+
+        let dir = tempdir().unwrap();
+        let paths: Vec<_> = (1..=2).map(|i| dir.path().join(format!("subset_{}.csv", i))).collect();
+        let files: Vec<&str> = paths.iter().map(|path| path.to_str().unwrap()).collect();
+        let counts = distribute_data(rows, 5, header, &files, None, None, SplitStrategy::RoundRobin).unwrap();
+
+        assert_eq!(counts, vec![3, 2]);
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "GameID,White\n1,Player1\n3,Player3\n5,Player5\n");
+        assert_eq!(std::fs::read_to_string(&paths[1]).unwrap(), "GameID,White\n2,Player2\n4,Player4\n");
+    }
+
+    #[test]
+    fn test_shuffle_rows() {
+        let rows: Vec<String> = (1..=20).map(|id| format!("{},Player{}", id, id)).collect();
+
+        let shuffled = shuffle_rows(rows.clone(), 42);
+        assert_eq!(shuffle_rows(rows.clone(), 42), shuffled);
+        assert_ne!(shuffled, rows);
+        let mut sorted = shuffled.clone();
+        sorted.sort_by_key(|row| row.split(',').next().unwrap().parse::<u32>().unwrap());
+        assert_eq!(sorted, rows);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("subset.csv");
+        let counts = distribute_data(shuffled.into_iter().map(Ok), 20, "GameID,White", &[file_path.to_str().unwrap()], None, None, SplitStrategy::Contiguous).unwrap();
+        assert_eq!(counts, vec![20]);
+        assert!(std::fs::read_to_string(&file_path).unwrap().starts_with("GameID,White\n"));
+    }
+
+    #[test]
+    fn test_distribute_data_mmap_matches_line_reader() {
+        let header = "GameID,White,Black,Opening,Result,Site";
+        let first = format!(
+            "{}\n{}\n{}\n{}\n{}\n\n{}\n",
+            header,
+            "1,Player1,Player2,Sicilian,1-0,lichess",
+            "2,Player3,Player4,\"Ruy Lopez, Morphy Defense\",0-1,lichess",
+            "3,\"Player \"\"Five\"\"\",Player6,,1/2-1/2,lichess\r",
+            "4,Player7,Player8",
+            "5,Jos\u{e9},Player9,Caro-Kann,1-0,lichess,extra",
+        );
+        let second = format!("{}\r\n{}\r\n{}", header, "6,Player1,Player3,French,0-1,lichess", "7,Player2,,Dutch,,");
+
+        let dir = tempdir().unwrap();
+        let inputs = [dir.path().join("first.csv"), dir.path().join("second.csv")];
+        std::fs::write(&inputs[0], &first).unwrap();
+        std::fs::write(&inputs[1], &second).unwrap();
+        let input_files: Vec<&str> = inputs.iter().map(|path| path.to_str().unwrap()).collect();
+        let rows = || first.lines().skip(1).chain(second.lines().skip(1)).map(|line| Ok(line.to_string())).collect::<Vec<_>>();
+        let total_rows = rows().len();
+        assert_eq!(total_rows, 8);
+
+        let picked = ["Result".to_string(), "White".to_string()];
+        for columns in [None, Some(&picked[..])] {
+            for split in [SplitStrategy::Contiguous, SplitStrategy::RoundRobin] {
+                let line_paths: Vec<_> = (1..=3).map(|i| dir.path().join(format!("lines_{}.csv", i))).collect();
+                let mmap_paths: Vec<_> = (1..=3).map(|i| dir.path().join(format!("mmap_{}.csv", i))).collect();
+                let line_files: Vec<&str> = line_paths.iter().map(|path| path.to_str().unwrap()).collect();
+                let mmap_files: Vec<&str> = mmap_paths.iter().map(|path| path.to_str().unwrap()).collect();
+
+                let line_counts = distribute_data(rows(), total_rows, header, &line_files, columns, None, split).unwrap();
+                let mmap_counts = distribute_data_mmap(&input_files, total_rows, header, &mmap_files, columns, split).unwrap();
+
+                assert_eq!(mmap_counts, line_counts);
+                for (line_path, mmap_path) in line_paths.iter().zip(&mmap_paths) {
+                    assert_eq!(std::fs::read(mmap_path).unwrap(), std::fs::read(line_path).unwrap());
+                }
             }
-            row_index += 1;
         }
-        writer.flush()?;
     }
 
-    println!("Data writing complete. {} rows distributed.", row_index);
-    Ok(())
+    // Not run by default: `cargo test --release -- --ignored --nocapture bench_distribute_data_mmap`
+    #[test]
+    #[ignore]
+    fn bench_distribute_data_mmap() {
+        use std::io::{BufRead, BufReader, Write};
+
+        const ROWS: usize = 400_000;
+        let header = "Event,White,Black,Result,UTCDate,WhiteElo,BlackElo,ECO,Opening,TimeControl,Termination,AN";
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("games.csv");
+        let mut file = BufWriter::new(File::create(&input).unwrap());
+        writeln!(file, "{}", header).unwrap();
+        for i in 0..ROWS {
+            // Every tenth row has a quoted opening, so both paths of the mmap
+            // reader get used.
+            let opening = if i % 10 == 0 { "\"Ruy Lopez, Morphy Defense\"" } else { "Sicilian Defense" };
+            writeln!(
+                file,
+                "Blitz,Player{},Player{},{},2016.06.30,{},{},B20,{},300+0,Normal,1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 a6",
+                i % 5_000,
+                (i * 7 + 1) % 5_000,
+                ["1-0", "0-1", "1/2-1/2"][i % 3],
+                1500 + i % 700,
+                1500 + (i * 3) % 700,
+                opening,
+            )
+            .unwrap();
+        }
+        file.flush().unwrap();
+        drop(file);
+        let input_file = input.to_str().unwrap();
+
+        let line_paths: Vec<_> = (1..=4).map(|i| dir.path().join(format!("lines_{}.csv", i))).collect();
+        let mmap_paths: Vec<_> = (1..=4).map(|i| dir.path().join(format!("mmap_{}.csv", i))).collect();
+        let line_files: Vec<&str> = line_paths.iter().map(|path| path.to_str().unwrap()).collect();
+        let mmap_files: Vec<&str> = mmap_paths.iter().map(|path| path.to_str().unwrap()).collect();
+
+        let start = std::time::Instant::now();
+        let rows = BufReader::new(File::open(&input).unwrap()).lines().skip(1).map(|line| line.map_err(Into::into));
+        let line_counts = distribute_data(rows, ROWS, header, &line_files, None, None, SplitStrategy::Contiguous).unwrap();
+        let line_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let mmap_counts = distribute_data_mmap(&[input_file], ROWS, header, &mmap_files, None, SplitStrategy::Contiguous).unwrap();
+        let mmap_time = start.elapsed();
+
+        assert_eq!(mmap_counts, line_counts);
+        for (line_path, mmap_path) in line_paths.iter().zip(&mmap_paths) {
+            assert_eq!(std::fs::read(mmap_path).unwrap(), std::fs::read(line_path).unwrap());
+        }
+        println!("{} rows, {:.1} MB", ROWS, std::fs::metadata(&input).unwrap().len() as f64 / 1e6);
+        println!("distribute_data (line reader): {:?}", line_time);
+        println!("distribute_data_mmap: {:?}", mmap_time);
+    }
+
+    #[test]
+    fn test_distribute_data_selected_columns() {
+        let header = "GameID,White,Black,Result";
+        let rows = vec![Ok("1,Player1,Player2,1-0".to_string())];
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("subset.csv");
+        let columns = vec!["Result".to_string(), "White".to_string()];
+
+        distribute_data(rows, 1, header, &[file_path.to_str().unwrap()], Some(&columns), None, SplitStrategy::Contiguous).unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "White,Result\nPlayer1,1-0\n");
+
+        let columns = vec!["White".to_string(), "WhiteElo".to_string(), "ECO".to_string()];
+        let err = distribute_data(Vec::new(), 1, header, &[file_path.to_str().unwrap()], Some(&columns), None, SplitStrategy::Contiguous).unwrap_err();
+        assert!(err.to_string().contains("[WhiteElo, ECO]"));
+    }
+
+    #[test]
+    fn test_distribute_data_skips_bad_rows() {
+        let header = "GameID,White,Black";
+        let rows = vec![
+            Ok("1,Player1,Player2".to_string()),
+            Ok("2,Player3".to_string()),
+            Ok("3,Player4,Player5".to_string()),
+        ];
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("subset.csv");
+        let mut skipped = Vec::new();
+
+        let counts = distribute_data(rows, 3, header, &[file_path.to_str().unwrap()], None, Some(&mut skipped), SplitStrategy::Contiguous).unwrap();
+
+        assert_eq!(counts, vec![2]);
+        assert_eq!(skipped, vec![(2, "expected 3 fields, found 2".to_string())]);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "GameID,White,Black\n1,Player1,Player2\n3,Player4,Player5\n"
+        );
+    }
+
+    #[test]
+    fn test_match_header_and_reorder_row() {
+        assert_eq!(match_header("GameID,White,Black", "GameID,White,Black").unwrap(), HeaderMatch::Same);
+
+        let mapping = match match_header("GameID,White,Black", "Black,GameID,White").unwrap() {
+            HeaderMatch::Reordered(mapping) => mapping,
+            HeaderMatch::Same => panic!("expected a reordered header"),
+        };
+        assert_eq!(mapping, vec![1, 2, 0]);
+        assert_eq!(reorder_row("Player2,1,\"Player, One\"", &mapping).unwrap(), "1,\"Player, One\",Player2");
+
+        let err = match_header("GameID,White,Black", "GameID,White,Result").unwrap_err().to_string();
+        assert!(err.contains("missing columns: [Black]"));
+        assert!(err.contains("unexpected columns: [Result]"));
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let df = df!(
+            "GameID" => &["g1", "g2"],
+            "WhiteElo" => &[1500i64, 1620],
+            "Result" => &["1-0", "1/2-1/2"]
+        )
+        .unwrap();
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("combined.parquet");
+        write_combined_parquet(&df, file_path.to_str().unwrap()).unwrap();
+
+        let read_back = read_combined_parquet(file_path.to_str().unwrap()).unwrap();
+        assert!(read_back.frame_equal(&df));
+    }
 }
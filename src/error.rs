@@ -0,0 +1,76 @@
+use std::fmt;
+use polars::prelude::PolarsError;
+
+#[derive(Debug)]
+pub enum AnalysisError {
+    MissingColumn(String),
+    Csv(csv::Error),
+    Io(std::io::Error),
+    Polars(PolarsError),
+    Json(serde_json::Error),
+    Parse(String),
+    Convergence(String),
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::MissingColumn(column) => write!(f, "missing column: {}", column),
+            AnalysisError::Csv(err) => write!(f, "csv error: {}", err),
+            AnalysisError::Io(err) => write!(f, "io error: {}", err),
+            AnalysisError::Polars(err) => write!(f, "polars error: {}", err),
+            AnalysisError::Json(err) => write!(f, "json error: {}", err),
+            AnalysisError::Parse(msg) => write!(f, "parse error: {}", msg),
+            AnalysisError::Convergence(msg) => write!(f, "{} did not converge", msg),
+            #[cfg(feature = "sqlite")]
+            AnalysisError::Sqlite(err) => write!(f, "sqlite error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AnalysisError::Csv(err) => Some(err),
+            AnalysisError::Io(err) => Some(err),
+            AnalysisError::Polars(err) => Some(err),
+            AnalysisError::Json(err) => Some(err),
+            #[cfg(feature = "sqlite")]
+            AnalysisError::Sqlite(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for AnalysisError {
+    fn from(err: csv::Error) -> Self {
+        AnalysisError::Csv(err)
+    }
+}
+
+impl From<std::io::Error> for AnalysisError {
+    fn from(err: std::io::Error) -> Self {
+        AnalysisError::Io(err)
+    }
+}
+
+impl From<PolarsError> for AnalysisError {
+    fn from(err: PolarsError) -> Self {
+        AnalysisError::Polars(err)
+    }
+}
+
+impl From<serde_json::Error> for AnalysisError {
+    fn from(err: serde_json::Error) -> Self {
+        AnalysisError::Json(err)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for AnalysisError {
+    fn from(err: rusqlite::Error) -> Self {
+        AnalysisError::Sqlite(err)
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use chrono::NaiveDate;
+use crate::analysis::{Game, GameResult};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterOptions {
+    pub min_elo: Option<u32>,
+    pub max_elo: Option<u32>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub exclude_tos: bool,
+    pub decisive_only: bool,
+}
+
+pub fn apply_filters(games: Vec<Game>, options: &FilterOptions) -> Vec<Game> {
+    let games = filter_by_elo(games, options.min_elo, options.max_elo);
+    let games = if options.exclude_tos { filter_out_tos_violations(games) } else { games };
+    let games = if options.decisive_only { filter_decisive(games) } else { games };
+
+    let date_bounds_set = options.from.is_some() || options.to.is_some();
+    if date_bounds_set && !games.is_empty() && games.iter().all(|game| game.date.is_none()) {
+        log::warn!("--from/--to ignored because the input has no Date or UTCDate column");
+        return games;
+    }
+    filter_by_date_range(games, options.from, options.to)
+}
+
+// Keeps the first occurrence of each game_id. Games without an id can't be
+// told apart, so they are always kept.
+pub fn deduplicate_games(games: Vec<Game>) -> (Vec<Game>, usize) {
+    let original_count = games.len();
+    let mut seen_ids = HashSet::new();
+
+    let unique_games: Vec<Game> = games
+        .into_iter()
+        .filter(|game| game.game_id.is_empty() || seen_ids.insert(game.game_id.clone()))
+        .collect();
+
+    let removed = original_count - unique_games.len();
+    (unique_games, removed)
+}
+
+fn elo_in_range(elo: Option<u32>, min: Option<u32>, max: Option<u32>) -> bool {
+    match elo {
+        Some(elo) => min.unwrap_or(0) <= elo && elo <= max.unwrap_or(u32::MAX),
+        None => min.is_none() && max.is_none(),
+    }
+}
+
+// Both players must fall inside the inclusive bounds. A missing Elo can't be
+// shown to be in range, so it excludes the game whenever a bound is set.
+pub fn filter_by_elo(games: Vec<Game>, min: Option<u32>, max: Option<u32>) -> Vec<Game> {
+    games
+        .into_iter()
+        .filter(|game| elo_in_range(game.white_elo, min, max) && elo_in_range(game.black_elo, min, max))
+        .collect()
+}
+
+// Drops games where either player was flagged for a terms of service
+// violation, since those may have been cheated. A missing flag counts as
+// no violation.
+pub fn filter_out_tos_violations(games: Vec<Game>) -> Vec<Game> {
+    games
+        .into_iter()
+        .filter(|game| game.white_tos_violation != Some(true) && game.black_tos_violation != Some(true))
+        .collect()
+}
+
+// Keeps only games with a winner, dropping draws and games whose result
+// can't be read, for rankings based purely on wins and losses.
+pub fn filter_decisive(games: Vec<Game>) -> Vec<Game> {
+    games
+        .into_iter()
+        .filter(|game| matches!(GameResult::from_game(game), Some(GameResult::WhiteWin | GameResult::BlackWin)))
+        .collect()
+}
+
+// Lichess writes dates as 2016.06.30 (PGN style); ISO 2016-06-30 is accepted too.
+pub fn parse_game_date(date: &str) -> Option<NaiveDate> {
+    let date = date.trim();
+    NaiveDate::parse_from_str(date, "%Y.%m.%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .ok()
+}
+
+// Inclusive on both ends. Like the Elo filter, a game whose date is missing
+// or unreadable is excluded as soon as either bound is set.
+pub fn filter_by_date_range(games: Vec<Game>, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Vec<Game> {
+    if from.is_none() && to.is_none() {
+        return games;
+    }
+
+    games
+        .into_iter()
+        .filter(|game| match game.date.as_deref().and_then(parse_game_date) {
+            Some(date) => from.unwrap_or(NaiveDate::MIN) <= date && date <= to.unwrap_or(NaiveDate::MAX),
+            None => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deduplicate_games() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "1".to_string(),
+                white: "Player2".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player3".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let (games, removed) = deduplicate_games(games);
+        assert_eq!(removed, 1);
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].white, "Player1");
+        assert_eq!(games[1].game_id, "2");
+    }
+
+    fn elo_games() -> Vec<Game> {
+        vec![
+            Game {
+                game_id: "1".to_string(),
+                white_elo: Some(1800),
+                black_elo: Some(1900),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white_elo: Some(2100),
+                black_elo: Some(2050),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white_elo: Some(2000),
+                black_elo: Some(2500),
+                ..Default::default()
+            },
+            Game {
+                game_id: "4".to_string(),
+                white_elo: Some(2200),
+                black_elo: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn ids(games: &[Game]) -> Vec<&str> {
+        games.iter().map(|game| game.game_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_by_elo_lower_only() {
+        let games = filter_by_elo(elo_games(), Some(2000), None);
+        assert_eq!(ids(&games), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn test_filter_by_elo_upper_only() {
+        let games = filter_by_elo(elo_games(), None, Some(2100));
+        assert_eq!(ids(&games), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_filter_by_elo_both_bounds() {
+        let games = filter_by_elo(elo_games(), Some(1900), Some(2200));
+        assert_eq!(ids(&games), vec!["2"]);
+    }
+
+    #[test]
+    fn test_filter_by_elo_no_bounds() {
+        assert_eq!(filter_by_elo(elo_games(), None, None).len(), 4);
+    }
+
+    fn flagged_games() -> Vec<Game> {
+        [(Some(false), Some(false)), (Some(false), Some(true)), (None, None)]
+            .iter()
+            .enumerate()
+            .map(|(index, &(white_tos_violation, black_tos_violation))| Game {
+                game_id: (index + 1).to_string(),
+                white_tos_violation,
+                black_tos_violation,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_out_tos_violations() {
+        assert_eq!(ids(&filter_out_tos_violations(flagged_games())), vec!["1", "3"]);
+        let options = FilterOptions { exclude_tos: true, ..Default::default() };
+        assert_eq!(ids(&apply_filters(flagged_games(), &options)), vec!["1", "3"]);
+        assert_eq!(apply_filters(flagged_games(), &FilterOptions::default()).len(), 3);
+    }
+
+    #[test]
+    fn test_filter_decisive() {
+        let games = || -> Vec<Game> {
+            ["1-0", "1/2-1/2", "0-1", "*"]
+                .iter()
+                .enumerate()
+                .map(|(index, result)| Game {
+                    game_id: (index + 1).to_string(),
+                    result: result.to_string(),
+                    ..Default::default()
+                })
+                .collect()
+        };
+
+        assert_eq!(ids(&filter_decisive(games())), vec!["1", "3"]);
+        let options = FilterOptions { decisive_only: true, ..Default::default() };
+        assert_eq!(ids(&apply_filters(games(), &options)), vec!["1", "3"]);
+    }
+
+    fn dated_games() -> Vec<Game> {
+        ["2016.06.29", "2016.06.30", "2016-07-01", "2016.07.02", "not a date"]
+            .iter()
+            .enumerate()
+            .map(|(index, date)| Game {
+                game_id: (index + 1).to_string(),
+                date: Some(date.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn date(value: &str) -> Option<NaiveDate> {
+        Some(NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap())
+    }
+
+    #[test]
+    fn test_filter_by_date_range_inclusive_bounds() {
+        let games = filter_by_date_range(dated_games(), date("2016-06-30"), date("2016-07-01"));
+        assert_eq!(ids(&games), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn test_filter_by_date_range_open_ended() {
+        assert_eq!(ids(&filter_by_date_range(dated_games(), date("2016-07-01"), None)), vec!["3", "4"]);
+        assert_eq!(ids(&filter_by_date_range(dated_games(), None, date("2016-06-29"))), vec!["1"]);
+        assert_eq!(filter_by_date_range(dated_games(), None, None).len(), 5);
+    }
+
+    #[test]
+    fn test_apply_filters_without_dates_ignores_date_range() {
+        let options = FilterOptions { from: date("2016-07-01"), ..Default::default() };
+        assert_eq!(apply_filters(elo_games(), &options).len(), 4);
+    }
+}
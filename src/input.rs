@@ -0,0 +1,44 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use flate2::read::MultiGzDecoder;
+
+// Opens an input CSV, decompressing on the fly when the name ends in `.gz`
+// (Lichess ships its exports as .csv.gz). Anything else is read as plain text.
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    if is_gzip(path) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+pub fn is_gzip(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("gz"))
+        .unwrap_or(false)
+}
+
+// What kind of records an input holds, going by its extension with any `.gz`
+// taken off. Anything that isn't a PGN, NDJSON or Parquet file is read as
+// CSV. Parquet is compressed already, so `.parquet.gz` isn't recognized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Csv,
+    Pgn,
+    Ndjson,
+    Parquet,
+}
+
+pub fn input_format(path: &Path) -> InputFormat {
+    if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("parquet")) {
+        return InputFormat::Parquet;
+    }
+    let name = if is_gzip(path) { path.file_stem().map(Path::new) } else { Some(path) };
+    match name.and_then(Path::extension).and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("pgn") => InputFormat::Pgn,
+        Some(extension) if extension.eq_ignore_ascii_case("ndjson") || extension.eq_ignore_ascii_case("jsonl") => InputFormat::Ndjson,
+        _ => InputFormat::Csv,
+    }
+}
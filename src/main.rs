@@ -1,11 +1,12 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::BufRead;
-use std::path::Path;
+use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
 use polars::prelude::*;
-use std::io::BufReader;
 use csv::Writer;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+use petgraph::graph::{DiGraph, NodeIndex};
+use rayon::prelude::*;
 
 
 
@@ -13,205 +14,1174 @@ mod data_distribution;
 mod column_info;
 mod analysis;
 mod strategy_analysis;
+mod cli;
+mod config;
+mod error;
+mod moves;
+mod filters;
+mod progress;
+mod input;
+mod data_cleaning;
+mod pgn;
+#[cfg(feature = "sqlite")]
+mod sqlite_export;
+
+use cli::OutputFormat;
+use input::InputFormat;
+use error::AnalysisError;
+use filters::FilterOptions;
+use analysis::GraphOptions;
+
+// Logs go to stderr so stdout only carries results. Warnings always show,
+// --verbose adds the debug output, and RUST_LOG overrides either.
+fn init_logging(verbose: bool) {
+    let level = if verbose { log::LevelFilter::Debug } else { log::LevelFilter::Warn };
+    // Only fails if a logger is already set, which the tests do more than once.
+    let _ = env_logger::Builder::new().filter_level(level).parse_default_env().try_init();
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let current_dir = std::env::current_dir()?;
-    let input_files = [
-        current_dir.join(Path::new("game1.csv")),
-        current_dir.join(Path::new("game2.csv")),
-    ];
-    let output_files = [
-        current_dir.join(Path::new("subset_data_1.csv")),
-        current_dir.join(Path::new("subset_data_2.csv")),
-        current_dir.join(Path::new("subset_data_3.csv")),
-        current_dir.join(Path::new("subset_data_4.csv")),
-        current_dir.join(Path::new("subset_data_5.csv")),
-    ];
+    let args = cli::parse_args(std::env::args().skip(1))?;
+    init_logging(args.verbose);
+    progress::set_quiet(args.quiet);
+    let output_files = args.subset_files();
+
+    // PGN, NDJSON and Parquet files are read straight into games by the analysis, so
+    // only the CSV inputs are cleaned and split into subsets.
+    let (csv_inputs, game_inputs): (Vec<PathBuf>, Vec<PathBuf>) = args
+        .inputs
+        .iter()
+        .cloned()
+        .partition(|file| input::input_format(file) == InputFormat::Csv);
 
+    // Cleaned copies live in a temp dir that is removed when main returns.
+    let clean_dir = tempfile::tempdir()?;
+    let csv_inputs = if args.clean {
+        data_cleaning::clean_files(&csv_inputs, clean_dir.path())?
+    } else {
+        csv_inputs
+    };
+    let input_files: Vec<PathBuf> = csv_inputs.iter().chain(&game_inputs).cloned().collect();
+
+    if args.dry_run {
+        let input_paths = input_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>();
+        return dry_run(&input_paths, args.reorder_columns);
+    }
+    if let Some((from, to)) = &args.path {
+        let input_paths = input_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>();
+        return print_path(&input_paths, from, to, &args.filters, &args.graph);
+    }
+    if let Some(n) = args.active {
+        let input_paths = input_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>();
+        return print_most_active(&input_paths, n, &args.filters);
+    }
+    if let Some((old, new)) = &args.diff {
+        std::fs::create_dir_all(&args.out_dir)?;
+        return write_graph_diff(old, new, &args.out_dir.join("graph_diff.csv"), &args.filters, &args.graph);
+    }
+    std::fs::create_dir_all(&args.out_dir)?;
 
     for file in &input_files {
-        println!("{}", file.display());
+        log::debug!("Input file: {}", file.display());
     }
-    
 
     for file in &output_files {
-        println!("{}", file.display());
+        log::debug!("Subset file: {}", file.display());
     }
 
-    column_info::print_column_info(&input_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>())?;
+    let mut manifest = Manifest::default();
+    let mut analysis_files = Vec::new();
+    // Every subset is analyzed, in parallel, each into its own out/<stem>/.
+    if !csv_inputs.is_empty() && split_csv_inputs(&args, &csv_inputs, &mut manifest)? {
+        analysis_files.extend(output_files);
+    }
+    analysis_files.extend(game_inputs);
+    if analysis_files.is_empty() {
+        return Ok(());
+    }
 
-    let (header, combined_data) = combine_csv_files(&input_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>())?;
+    let export_options = ExportOptions {
+        format: args.format,
+        top_n: args.top_n,
+        sqlite_path: args.sqlite.as_deref(),
+        wide: args.wide,
+    };
+    let analysis_paths = analysis_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>();
+    perform_game_data_analysis(&analysis_paths, &args.out_dir, &args.filters, &args.graph, &export_options, &mut manifest)?;
+    manifest.write(&args.out_dir.join("manifest.json"))?;
+    Ok(())
+}
 
-    data_distribution::distribute_data(&combined_data, &header, &output_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>())?;
+// Splits the rows of every CSV input across the subset files, adding them to
+// `manifest`. Returns false, having written nothing, when the inputs have no
+// data rows.
+fn split_csv_inputs(args: &cli::Args, csv_inputs: &[PathBuf], manifest: &mut Manifest) -> Result<bool, Box<dyn Error>> {
+    let output_files = args.subset_files();
+    let input_paths = csv_inputs.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>();
+    column_info::print_column_info(&input_paths)?;
 
-    
-    let analysis_output_file = current_dir.join("analysis_output.csv");
-    perform_game_data_analysis(&[output_files[0].to_str().unwrap()], &analysis_output_file)?;    
+    let (header, total_rows) = scan_csv_files(&input_paths, args.reorder_columns)?;
+    if total_rows == 0 {
+        log::warn!("The CSV inputs contain no data rows, nothing to distribute");
+        return Ok(false);
+    }
+
+    let subset_paths = output_files.iter().map(|p| p.to_str().unwrap_or_default()).collect::<Vec<_>>();
+    let mmap_supported = !args.shuffle && !args.skip_bad_rows && !csv_inputs.iter().any(|file| input::is_gzip(file));
+    if args.mmap && !mmap_supported {
+        log::warn!("--mmap needs uncompressed inputs and doesn't work with --shuffle or --skip-bad-rows, reading the inputs line by line instead");
+    }
+    let mut skipped_rows = Vec::new();
+    if args.mmap && mmap_supported {
+        data_distribution::distribute_data_mmap(&input_paths, total_rows, &header, &subset_paths, args.columns.as_deref(), args.split)?;
+    } else {
+        let rows: Box<dyn Iterator<Item = Result<String, Box<dyn Error>>>> = if args.shuffle {
+            let rows = combined_rows(&input_paths, &header).collect::<Result<Vec<_>, _>>()?;
+            Box::new(data_distribution::shuffle_rows(rows, args.graph.sample_seed()).into_iter().map(Ok))
+        } else {
+            Box::new(combined_rows(&input_paths, &header))
+        };
+        data_distribution::distribute_data(rows, total_rows, &header, &subset_paths, args.columns.as_deref(), args.skip_bad_rows.then_some(&mut skipped_rows), args.split)?;
+    }
+    report_skipped_rows(&skipped_rows);
+    for (i, subset_path) in subset_paths.iter().enumerate() {
+        manifest.add_with_header(subset_path, &format!("subset {} of the input rows", i + 1))?;
+    }
+
+    if args.parquet {
+        for file in &output_files {
+            let parquet_file = data_distribution::write_subset_parquet(file)?;
+            log::info!("Wrote {}", parquet_file.display());
+            manifest.add(&parquet_file.to_string_lossy(), "the subset next to it as parquet")?;
+        }
+    }
+    Ok(true)
+}
+
+// Lists the first few rows --skip-bad-rows left out so a run with many bad
+// rows doesn't flood the log.
+fn report_skipped_rows(skipped_rows: &[(usize, String)]) {
+    const SHOWN: usize = 10;
+    if skipped_rows.is_empty() {
+        return;
+    }
+    log::warn!("Skipped {} malformed row(s)", skipped_rows.len());
+    for (row_number, reason) in skipped_rows.iter().take(SHOWN) {
+        log::warn!("  data row {}: {}", row_number, reason);
+    }
+    if skipped_rows.len() > SHOWN {
+        log::warn!("  ... and {} more", skipped_rows.len() - SHOWN);
+    }
+}
+
+// Runs the input checks of a normal run and reports what it found, without
+// writing anything. Missing required columns are an error so the process
+// exits nonzero.
+fn dry_run(input_files: &[&str], reorder_columns: bool) -> Result<(), Box<dyn Error>> {
+    let (csv_files, game_files): (Vec<&str>, Vec<&str>) = input_files
+        .iter()
+        .partition(|file| input::input_format(Path::new(file)) == InputFormat::Csv);
+
+    if !csv_files.is_empty() {
+        column_info::print_column_info(&csv_files)?;
+        let (header, total_rows) = scan_csv_files(&csv_files, reorder_columns)?;
+        println!("Dry run: {} CSV file(s), {} data row(s)", csv_files.len(), total_rows);
+
+        let columns = data_distribution::parse_line(&header)?;
+        let missing: Vec<&str> = analysis::REQUIRED_COLUMNS
+            .iter()
+            .copied()
+            .filter(|required| !columns.iter().any(|column| column == *required))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("missing required column(s): {}", missing.join(", ")).into());
+        }
+    }
+    for game_file in game_files {
+        let games = load_games(game_file, &FilterOptions::default())?;
+        println!("Dry run: {} has {} game(s)", game_file, games.len());
+    }
+
+    println!("Dry run: inputs look valid, no files were written");
+    Ok(())
+}
+
+// Prints the `n` players with the most games over every input, without
+// splitting the inputs or writing anything.
+fn print_most_active(input_files: &[&str], n: usize, filters: &FilterOptions) -> Result<(), Box<dyn Error>> {
+    let mut games = Vec::new();
+    for input_file in input_files {
+        games.extend(load_games(input_file, filters)?);
+    }
+    for (rank, (player, games_played)) in analysis::most_active_players(&games, n).into_iter().enumerate() {
+        println!("{}. {} ({} games)", rank + 1, player, games_played);
+    }
+    Ok(())
+}
+
+// Builds a graph from each of two datasets and writes what changed between
+// them to `output_file`, without splitting anything or running the analysis.
+fn write_graph_diff(old: &Path, new: &Path, output_file: &Path, filters: &FilterOptions, graph_options: &GraphOptions) -> Result<(), Box<dyn Error>> {
+    let old_graph = analysis::build_graph_with(&load_games(&old.to_string_lossy(), filters)?, graph_options);
+    let new_graph = analysis::build_graph_with(&load_games(&new.to_string_lossy(), filters)?, graph_options);
+    let diff = analysis::graph_diff(&old_graph, &new_graph);
+    analysis::export_graph_diff(&diff, &output_file.to_string_lossy())?;
+    println!(
+        "{} player(s) added, {} removed and {} edge(s) changed, written to {}",
+        diff.added_players.len(),
+        diff.removed_players.len(),
+        diff.changed_edges.len(),
+        output_file.display()
+    );
+    Ok(())
+}
+
+// Builds one graph from every input and prints the shortest chain of players
+// from `from` to `to`, then their direct record against each other, without
+// splitting the inputs or writing anything.
+fn print_path(input_files: &[&str], from: &str, to: &str, filters: &FilterOptions, graph_options: &GraphOptions) -> Result<(), Box<dyn Error>> {
+    let mut games = Vec::new();
+    for input_file in input_files {
+        games.extend(load_games(input_file, filters)?);
+    }
+    let graph = analysis::build_graph_with(&games, graph_options);
+    match analysis::shortest_path_between(&graph, from, to) {
+        Some(path) => println!("{}", path.join(" -> ")),
+        None => println!("No path from {} to {}", from, to),
+    }
+    let (wins, losses, draws) = analysis::head_to_head(&games, from, to);
+    println!("{} against {}: {} win(s), {} loss(es), {} draw(s)", from, to, wins, losses, draws);
     Ok(())
 }
 
-fn combine_csv_files(files: &[&str]) -> Result<(String, Vec<String>), Box<dyn Error>> {
-    let mut combined_data = Vec::new();
+// Returns the first file's header and the number of data rows across all
+// files, without keeping any rows in memory. Every later header must match
+// the first; with `reorder_columns` a header with the same columns in another
+// order is accepted and `combined_rows` puts its rows back in order.
+fn scan_csv_files(files: &[&str], reorder_columns: bool) -> Result<(String, usize), Box<dyn Error>> {
     let mut header = String::new();
+    let mut total_rows = 0;
+    let mut progress = progress::Progress::new("Scanning input files");
 
+    // The first file that has a header sets it; completely empty files are
+    // skipped rather than compared against it.
+    let mut header_file = None;
     for file_path in files {
-        let file = File::open(Path::new(file_path))?;
-        let reader = BufReader::new(file);
-        let mut is_first_file = true;
+        let reader = input::open_input(Path::new(file_path))?;
+        let mut is_empty = true;
 
         for (index, line) in reader.lines().enumerate() {
             let line = line?;
-            if index == 0 && is_first_file {
-                header = line;
-                is_first_file = false;
+            is_empty = false;
+            if index == 0 {
+                match header_file {
+                    None => {
+                        header = line;
+                        header_file = Some(*file_path);
+                    }
+                    Some(header_file) => check_header(&header, &line, header_file, file_path, reorder_columns)?,
+                }
             } else {
-                combined_data.push(line);
+                total_rows += 1;
+                progress.tick();
             }
         }
+        if is_empty {
+            log::warn!("{} is empty, skipping it", file_path);
+        }
     }
+    progress.finish();
 
-    Ok((header, combined_data))
+    Ok((header, total_rows))
+}
+
+fn check_header(reference: &str, header: &str, reference_file: &str, file: &str, reorder_columns: bool) -> Result<(), Box<dyn Error>> {
+    let header_match = data_distribution::match_header(reference, header)
+        .map_err(|err| format!("{} does not have the same columns as {}: {}", file, reference_file, err))?;
+    match header_match {
+        data_distribution::HeaderMatch::Reordered(mapping) if !reorder_columns => {
+            let reference = reference.split(',').collect::<Vec<_>>();
+            let moved: Vec<&str> = mapping
+                .iter()
+                .enumerate()
+                .filter(|(index, &position)| *index != position)
+                .filter_map(|(index, _)| reference.get(index).copied())
+                .collect();
+            Err(format!(
+                "{} has its columns in a different order from {} (moved: [{}]); pass --reorder-columns to combine them by name",
+                file,
+                reference_file,
+                moved.join(", ")
+            )
+            .into())
+        }
+        _ => Ok(()),
+    }
 }
 
-fn perform_game_data_analysis(input_files: &[&str], output_file: &Path) -> Result<(), Box<dyn Error>> {
-    std::fs::create_dir_all("./out")?;
+// Streams the data rows of every file in order, skipping each file's header.
+// Rows from files whose columns are in a different order from `header` are
+// rewritten to match it.
+fn combined_rows<'a>(files: &'a [&str], header: &'a str) -> impl Iterator<Item = Result<String, Box<dyn Error>>> + 'a {
+    files.iter().flat_map(move |file_path| {
+        let rows: Box<dyn Iterator<Item = Result<String, Box<dyn Error>>>> = match input::open_input(Path::new(file_path)) {
+            Ok(reader) => {
+                let mut lines = reader.lines();
+                let file_header = match lines.next().transpose() {
+                    Ok(file_header) => file_header.unwrap_or_default(),
+                    Err(err) => return Box::new(std::iter::once(Err(err.into()))) as Box<dyn Iterator<Item = _>>,
+                };
+                match data_distribution::match_header(header, &file_header) {
+                    Ok(data_distribution::HeaderMatch::Reordered(mapping)) => Box::new(lines.map(move |line| {
+                        data_distribution::reorder_row(&line?, &mapping)
+                    })),
+                    _ => Box::new(lines.map(|line| line.map_err(Into::into))),
+                }
+            }
+            Err(err) => Box::new(std::iter::once(Err(err.into()))),
+        };
+        rows
+    })
+}
 
-    let pr_scores_file = "./out/pr_scores.csv";
-    let btw_scores_file = "./out/btw_scores.csv";
-    let cls_scores_file = "./out/cls_scores.csv";
-    let player_perf_file = "./out/player_perf.csv";
-    let in_out_degree_file = "./out/in_out_degree.csv";
-    let weighted_centrality_file = "./out/weighted_centrality.csv";
-    let mean_mode_metrics_file = "./out/mean_mode_metrics.csv";
+#[cfg(feature = "sqlite")]
+fn create_sqlite_tables(path: &Path) -> Result<(), Box<dyn Error>> {
+    sqlite_export::create_tables(path.to_str().unwrap_or_default())?;
+    Ok(())
+}
 
-    let mut output_writer = Writer::from_path(output_file)?;
+#[cfg(not(feature = "sqlite"))]
+fn create_sqlite_tables(_path: &Path) -> Result<(), Box<dyn Error>> {
+    Err("--sqlite needs the sqlite feature: cargo run --features sqlite".into())
+}
 
-    for input_file in input_files {
-        let file = File::open(input_file)?;
-        let reader = BufReader::new(file);
-        let df = CsvReader::new(reader)
-            .infer_schema(None)
-            .has_header(true)
-            .finish()?;
+#[cfg(feature = "sqlite")]
+fn export_sqlite(
+    path: &Path,
+    source: &str,
+    performance: &HashMap<String, analysis::PlayerPerformance>,
+    pagerank_scores: &HashMap<NodeIndex, f64>,
+    graph: &DiGraph<String, u32>,
+) -> Result<(), Box<dyn Error>> {
+    sqlite_export::export_to_sqlite(path.to_str().unwrap_or_default(), source, performance, pagerank_scores, graph)?;
+    Ok(())
+}
 
-        let games = analysis::read_games_from_dataframe(&df)?;
-        let graph = analysis::build_graph(&games);
-        let pagerank_scores = analysis::calculate_pagerank(&graph);
-        let betweenness_centrality = analysis::calculate_betweenness_centrality(&graph);
-        let closeness_centrality = analysis::calculate_closeness_centrality(&graph);
-        let performance = analysis::track_player_performance(&games);
+#[cfg(not(feature = "sqlite"))]
+fn export_sqlite(
+    _path: &Path,
+    _source: &str,
+    _performance: &HashMap<String, analysis::PlayerPerformance>,
+    _pagerank_scores: &HashMap<NodeIndex, f64>,
+    _graph: &DiGraph<String, u32>,
+) -> Result<(), Box<dyn Error>> {
+    Err("--sqlite needs the sqlite feature: cargo run --features sqlite".into())
+}
+
+fn export_scores(
+    format: OutputFormat,
+    scores: &HashMap<NodeIndex, f64>,
+    graph: &DiGraph<String, u32>,
+    filepath: &str,
+    top_n: Option<usize>,
+) -> Result<(), AnalysisError> {
+    match format {
+        OutputFormat::Csv => analysis::export_centrality_data(scores, graph, filepath, top_n),
+        OutputFormat::Json => analysis::export_centrality_data_json(scores, graph, filepath, top_n),
+    }
+}
+
+#[derive(Debug, Default)]
+struct FileAnalysis {
+    games: Vec<analysis::Game>,
+    graph: DiGraph<String, u32>,
+    pagerank_scores: HashMap<NodeIndex, f64>,
+    weighted_pagerank: HashMap<NodeIndex, f64>,
+    betweenness_centrality: HashMap<NodeIndex, f64>,
+    closeness_centrality: HashMap<NodeIndex, f64>,
+    harmonic_centrality: HashMap<NodeIndex, f64>,
+    eigenvector_centrality: HashMap<NodeIndex, f64>,
+    katz_centrality: HashMap<NodeIndex, f64>,
+    // Only computed with --recency-half-life.
+    recency_pagerank: Option<HashMap<NodeIndex, f64>>,
+    performance: HashMap<String, analysis::PlayerPerformance>,
+    in_out_degree_centrality: HashMap<NodeIndex, (usize, usize)>,
+    weighted_betweenness: HashMap<NodeIndex, f64>,
+    weighted_closeness: HashMap<NodeIndex, f64>,
+    mean_mode_metrics: HashMap<String, (f64, f64, f64, u32)>,
+    rating_volatility: HashMap<String, f64>,
+    rating_stats: HashMap<String, analysis::PlayerRatingStats>,
+    player_eco_classifications: HashMap<String, HashMap<String, u32>>,
+    components: Vec<Vec<String>>,
+    // Printed per file once the analyses are back, so the lines come out in
+    // input order.
+    reciprocity: f64,
+    elo_assortativity: f64,
+    upset_rate: f64,
+    dominance_chain: analysis::DominanceChain,
+    intransitive_triples: usize,
+}
+
+// Reads the deduplicated, filtered games of one CSV, PGN or NDJSON file
+// (gzipped or not) or Parquet file.
+fn load_games(input_file: &str, filters: &FilterOptions) -> Result<Vec<analysis::Game>, AnalysisError> {
+    Ok(load_games_with_frame(input_file, filters)?.games)
+}
+
+// A file's games, and for a CSV input the DataFrame they were read from as
+// long as it still holds exactly those games, i.e. deduplication and the
+// filters didn't drop any.
+struct LoadedGames {
+    games: Vec<analysis::Game>,
+    frame: Option<DataFrame>,
+}
+
+fn load_games_with_frame(input_file: &str, filters: &FilterOptions) -> Result<LoadedGames, AnalysisError> {
+    let (mut games, frame) = match input::input_format(Path::new(input_file)) {
+        InputFormat::Csv => read_csv_games(input_file)?,
+        InputFormat::Pgn => (pgn::read_games_from_pgn(input_file)?, None),
+        InputFormat::Ndjson => (analysis::read_games_from_ndjson(input_file)?, None),
+        InputFormat::Parquet => games_from_frame(data_distribution::read_combined_parquet(input_file)?)?,
+    };
+    if games.is_empty() {
+        log::warn!("{} has no games", input_file);
+        return Ok(LoadedGames { games, frame: None });
+    }
+
+    let scheme = analysis::detect_result_scheme(&games);
+    log::info!("Reading the results in {} as {:?}", input_file, scheme);
+    analysis::normalize_results(&mut games, scheme);
+    let (games, duplicates) = filters::deduplicate_games(games);
+    if duplicates > 0 {
+        log::info!("Removed {} duplicate game(s) from {}", duplicates, input_file);
+    }
+    let games = filters::apply_filters(games, filters);
+    if games.is_empty() {
+        log::warn!("No games in {} are left after filtering", input_file);
+    }
+    // Both steps only ever drop games, so an unchanged count means nothing went.
+    let frame = frame.filter(|df| df.height() == games.len());
+    Ok(LoadedGames { games, frame })
+}
+
+fn read_csv_games(input_file: &str) -> Result<(Vec<analysis::Game>, Option<DataFrame>), AnalysisError> {
+    let mut bytes = Vec::new();
+    input::open_input(Path::new(input_file))?.read_to_end(&mut bytes)?;
+    let df = CsvReader::new(std::io::Cursor::new(bytes))
+        .infer_schema(None)
+        .has_header(true)
+        .finish()?;
+    games_from_frame(df)
+}
+
+fn games_from_frame(df: DataFrame) -> Result<(Vec<analysis::Game>, Option<DataFrame>), AnalysisError> {
+    // A header-only file gives a DataFrame with no rows, whose columns can't be
+    // relied on to have their usual types, so don't try to read games from it.
+    if df.height() == 0 {
+        return Ok((Vec::new(), None));
+    }
+    let games = analysis::read_games_from_dataframe(&df)?;
+    Ok((games, Some(df)))
+}
+
+// How far the dominance chain search walks when the player graph has cycles,
+// which it nearly always does once draws are edges both ways.
+const DOMINANCE_CHAIN_MAX_DEPTH: usize = 8;
+
+fn analyze_file(input_file: &str, filters: &FilterOptions, graph_options: &GraphOptions) -> Result<FileAnalysis, AnalysisError> {
+    let LoadedGames { games, frame } = load_games_with_frame(input_file, filters)?;
+    if games.is_empty() {
+        log::warn!("Skipping the analysis of {}", input_file);
+        return Ok(FileAnalysis::default());
+    }
+
+    let inconsistent = analysis::find_result_rating_inconsistencies(&games);
+    if !inconsistent.is_empty() {
+        log::warn!("{} game(s) in {} have a Result that contradicts their rating diffs", inconsistent.len(), input_file);
+    }
+    let undecided = analysis::players_without_decisive_games(&games);
+    if !undecided.is_empty() {
+        log::info!("{} player(s) in {} have no decisive games", undecided.len(), input_file);
+    }
+
+    let move_count_mismatches = moves::validate_move_counts(&games);
+    if !move_count_mismatches.is_empty() {
+        log::warn!("{} game(s) in {} have a TotalMoves value that disagrees with their move list", move_count_mismatches.len(), input_file);
+    }
+
+    // The columns give the same graph without walking the games again.
+    let graph = match frame {
+        Some(df) => analysis::build_graph_from_dataframe(&df, graph_options)?,
+        None => analysis::build_graph_with(&games, graph_options),
+    };
+    let pagerank_scores = analysis::calculate_pagerank(&graph);
+    let weighted_pagerank = analysis::calculate_weighted_pagerank(&graph);
+    let betweenness_centrality = analysis::calculate_betweenness_centrality(&graph, graph_options.betweenness_samples, graph_options.sample_seed());
+    let closeness_centrality = analysis::calculate_closeness_centrality(&graph);
+    let harmonic_centrality = analysis::calculate_harmonic_centrality(&graph);
+    // Power iteration often fails to settle on graphs with long one-way chains,
+    // which shouldn't take the rest of the analysis down with it.
+    let eigenvector_centrality = match analysis::calculate_eigenvector_centrality(&graph) {
+        Ok(scores) => scores,
+        Err(err) => {
+            log::warn!("Skipping eigenvector centrality for {}: {}", input_file, err);
+            HashMap::new()
+        }
+    };
+    let katz_centrality = match analysis::calculate_katz_centrality(&graph, analysis::DEFAULT_KATZ_ALPHA, analysis::DEFAULT_KATZ_BETA) {
+        Ok(scores) => scores,
+        Err(err) => {
+            log::warn!("Skipping Katz centrality for {}: {}", input_file, err);
+            HashMap::new()
+        }
+    };
+    let recency_pagerank = graph_options.recency_half_life.map(|half_life| recency_pagerank(&games, &graph, half_life, graph_options));
+    let performance = analysis::track_player_performance_with(&games, graph_options.names);
 
 
-        let in_out_degree_centrality = analysis::calculate_in_out_degree_centrality(&graph);
+    let in_out_degree_centrality = analysis::calculate_in_out_degree_centrality(&graph);
 
 
-        let (weighted_betweenness, weighted_closeness) = analysis::calculate_weighted_centrality(&graph);
+    let (weighted_betweenness, weighted_closeness) = analysis::calculate_weighted_centrality(&graph);
 
 
-        let mean_mode_metrics = analysis::calculate_mean_mode(&games);
+    let mean_mode_metrics = analysis::calculate_mean_mode(&games, graph_options.names);
+    let rating_volatility = analysis::rating_volatility(&games, graph_options.names);
+    let rating_stats = analysis::calculate_rating_stats(&games, graph_options.names);
 
 
-        let mut player_eco_classifications: HashMap<String, HashMap<String, u32>> = HashMap::new();
-        for (eco, game_group) in strategy_analysis::classify_games_by_eco(&games).iter() {
-            for game in game_group {
-                let white_player = &game.white;
-                let black_player = &game.black;
+    let player_eco_classifications = strategy_analysis::player_eco_counts(&games, graph_options.names);
 
-                player_eco_classifications
-                    .entry(white_player.clone())
-                    .or_insert_with(HashMap::new)
-                    .entry(eco.clone())
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
+    let components = analysis::connected_components(&graph);
+    let reciprocity = analysis::graph_reciprocity(&graph);
+    let elo_assortativity = analysis::elo_assortativity(&games);
+    let upset_rate = analysis::upset_rate(&games);
+    let dominance_chain = analysis::longest_dominance_chain(&graph, DOMINANCE_CHAIN_MAX_DEPTH);
+    let intransitive_triples = analysis::find_intransitive_triples(&graph).len();
 
-                player_eco_classifications
-                    .entry(black_player.clone())
-                    .or_insert_with(HashMap::new)
-                    .entry(eco.clone())
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
+    Ok(FileAnalysis {
+        games,
+        graph,
+        pagerank_scores,
+        weighted_pagerank,
+        betweenness_centrality,
+        closeness_centrality,
+        harmonic_centrality,
+        eigenvector_centrality,
+        katz_centrality,
+        recency_pagerank,
+        performance,
+        in_out_degree_centrality,
+        weighted_betweenness,
+        weighted_closeness,
+        mean_mode_metrics,
+        rating_volatility,
+        rating_stats,
+        player_eco_classifications,
+        components,
+        reciprocity,
+        elo_assortativity,
+        upset_rate,
+        dominance_chain,
+        intransitive_triples,
+    })
+}
+
+// The recency-weighted graph is built with the same options, so each of its
+// nodes has the name of exactly one node of `graph` and its scores are moved
+// across by name to be exported alongside the others.
+fn recency_pagerank(games: &[analysis::Game], graph: &DiGraph<String, u32>, half_life: usize, graph_options: &GraphOptions) -> HashMap<NodeIndex, f64> {
+    let nodes: HashMap<&str, NodeIndex> = graph.node_indices().map(|node| (graph[node].as_str(), node)).collect();
+    let recency_graph = analysis::build_graph_recency_weighted_with(games, half_life, graph_options);
+    analysis::calculate_recency_pagerank(&recency_graph)
+        .into_iter()
+        .filter_map(|(node, score)| nodes.get(recency_graph[node].as_str()).map(|&node| (node, score)))
+        .collect()
+}
+
+// Each input file is independent, so they are analyzed in parallel and the
+// results are returned in input order. Nothing is written here; exports
+// happen afterwards on a single thread so output paths never collide.
+fn analyze_files(input_files: &[&str], filters: &FilterOptions, graph_options: &GraphOptions) -> Result<Vec<FileAnalysis>, AnalysisError> {
+    input_files.par_iter().map(|input_file| analyze_file(input_file, filters, graph_options)).collect()
+}
+
+// Totals across every analyzed file, kept as the files are processed so the
+// summary block doesn't have to re-read the intermediate exports.
+#[derive(Debug, Default)]
+struct AnalysisSummary {
+    total_games: usize,
+    // Each file's draw rate weighted by its game count, so dividing by
+    // total_games gives the draw rate over every file.
+    drawn_games: f64,
+    players: HashSet<String>,
+    top_pagerank: Option<(String, f64)>,
+    // Decisive games won by the lower-rated player.
+    upsets: u32,
+    // Player who beat their Elo expectation by the most in any one file.
+    top_elo_delta: Option<(String, f64)>,
+    // Summed over the files' graphs, so the density is weighted by graph size.
+    edges: f64,
+    possible_edges: f64,
+}
+
+impl AnalysisSummary {
+    fn add(&mut self, games: &[analysis::Game], graph: &DiGraph<String, u32>, pagerank_scores: &HashMap<NodeIndex, f64>, upsets: &HashMap<String, u32>, elo_deltas: &HashMap<String, f64>) {
+        self.total_games += games.len();
+        self.upsets += upsets.values().sum::<u32>();
+        self.drawn_games += analysis::overall_draw_rate(games) * games.len() as f64;
+        self.players.extend(graph.node_weights().cloned());
+        let node_count = graph.node_count() as f64;
+        let possible_edges = node_count * (node_count - 1.0).max(0.0);
+        self.edges += analysis::graph_density(graph) * possible_edges;
+        self.possible_edges += possible_edges;
+        if let Some((top, score)) = analysis::top_player(pagerank_scores, graph) {
+            let is_better = match &self.top_pagerank {
+                Some((player, best)) => score > *best || (score == *best && top < player.as_str()),
+                None => true,
+            };
+            if is_better {
+                self.top_pagerank = Some((top.to_string(), score));
             }
         }
+        for (player, &delta) in elo_deltas {
+            let is_better = match &self.top_elo_delta {
+                Some((best_player, best)) => delta > *best || (delta == *best && player < best_player),
+                None => true,
+            };
+            if is_better {
+                self.top_elo_delta = Some((player.clone(), delta));
+            }
+        }
+    }
+
+    fn draw_percentage(&self) -> f64 {
+        if self.total_games == 0 {
+            0.0
+        } else {
+            self.drawn_games / self.total_games as f64 * 100.0
+        }
+    }
+
+    fn density(&self) -> f64 {
+        if self.possible_edges == 0.0 {
+            0.0
+        } else {
+            self.edges / self.possible_edges
+        }
+    }
+
+    fn write<W: std::io::Write>(&self, writer: &mut Writer<W>) -> Result<(), csv::Error> {
+        let (top_player, top_score) = match &self.top_pagerank {
+            Some((player, score)) => (player.clone(), score.to_string()),
+            None => (String::new(), String::new()),
+        };
+        writer.write_record(["Summary", "Total Games", &self.total_games.to_string(), "", "", "", ""])?;
+        writer.write_record(["Summary", "Distinct Players", &self.players.len().to_string(), "", "", "", ""])?;
+        writer.write_record(["Summary", "Draw Percentage", &format!("{:.2}", self.draw_percentage()), "", "", "", ""])?;
+        writer.write_record(["Summary", "Top PageRank Player", &top_player, &top_score, "", "", ""])?;
+        writer.write_record(["Summary", "Upsets", &self.upsets.to_string(), "", "", "", ""])?;
+        let (top_elo_player, top_elo_delta) = match &self.top_elo_delta {
+            Some((player, delta)) => (player.clone(), format!("{:.2}", delta)),
+            None => (String::new(), String::new()),
+        };
+        writer.write_record(["Summary", "Top Elo Delta Player", &top_elo_player, &top_elo_delta, "", "", ""])?;
+        writer.write_record(["Summary", "Graph Density", &format!("{:.4}", self.density()), "", "", "", ""])?;
+        Ok(())
+    }
+}
+
+// Where one input's per-metric exports go.
+struct MetricFiles {
+    pr_scores: String,
+    weighted_pr_scores: String,
+    btw_scores: String,
+    cls_scores: String,
+    eig_scores: String,
+    hrm_scores: String,
+    katz_scores: String,
+    recency_pr_scores: String,
+    player_perf: String,
+    in_out_degree: String,
+    weighted_centrality: String,
+    mean_mode_metrics: String,
+    head_to_head: String,
+    graph_dot: String,
+    graph_graphml: String,
+    adjacency_matrix: String,
+    roster: String,
+    components: String,
+    rating_stats: String,
+    rating_histogram: String,
+    opening_win_rate: String,
+    avg_moves: String,
+    draw_rates: String,
+    color_split: String,
+    opening_diversity: String,
+    eco_outcomes: String,
+    eco_groups: String,
+    player_table: String,
+    opening_transitions: String,
+    first_moves: String,
+}
+
+impl MetricFiles {
+    fn new(dir: &Path, extension: &str) -> MetricFiles {
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        MetricFiles {
+            pr_scores: path(&format!("pr_scores.{}", extension)),
+            weighted_pr_scores: path(&format!("weighted_pr_scores.{}", extension)),
+            btw_scores: path(&format!("btw_scores.{}", extension)),
+            cls_scores: path(&format!("cls_scores.{}", extension)),
+            eig_scores: path(&format!("eig_scores.{}", extension)),
+            hrm_scores: path(&format!("hrm_scores.{}", extension)),
+            katz_scores: path(&format!("katz_scores.{}", extension)),
+            recency_pr_scores: path(&format!("recency_pr_scores.{}", extension)),
+            player_perf: path(&format!("player_perf.{}", extension)),
+            in_out_degree: path("in_out_degree.csv"),
+            weighted_centrality: path("weighted_centrality.csv"),
+            mean_mode_metrics: path("mean_mode_metrics.csv"),
+            head_to_head: path("head_to_head.csv"),
+            graph_dot: path("player_graph.dot"),
+            graph_graphml: path("player_graph.graphml"),
+            adjacency_matrix: path("adjacency_matrix.csv"),
+            roster: path("roster.csv"),
+            components: path("components.csv"),
+            rating_stats: path("rating_stats.csv"),
+            rating_histogram: path("rating_histogram.csv"),
+            opening_win_rate: path("opening_win_rates.csv"),
+            avg_moves: path("avg_moves_by_time_control.csv"),
+            draw_rates: path("draw_rate_by_time_control.csv"),
+            color_split: path("color_split_performance.csv"),
+            opening_diversity: path("opening_diversity.csv"),
+            eco_outcomes: path("eco_outcomes.csv"),
+            eco_groups: path("eco_groups.csv"),
+            player_table: path("player_table.csv"),
+            opening_transitions: path("opening_transitions.csv"),
+            first_moves: path("first_moves.csv"),
+        }
+    }
+}
 
-        output_writer.write_record(&["Player", "ECO", "Count", "", "", "", ""])?;
-        for (player, eco_counts) in &player_eco_classifications {
-            for (eco, count) in eco_counts {
-                output_writer.write_record(&[player.as_str(), eco.as_str(), &count.to_string(), "", "", "", ""])?;
+// One subdirectory of `metrics_dir` per input, named after the file stem so
+// different datasets don't overwrite each other. Inputs that share a stem get
+// a numeric suffix.
+fn metrics_dirs(metrics_dir: &Path, input_files: &[&str]) -> Vec<PathBuf> {
+    let mut used = HashSet::new();
+    input_files
+        .iter()
+        .map(|input_file| {
+            let stem = Path::new(input_file)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "input".to_string());
+            let mut name = stem.clone();
+            let mut suffix = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{}_{}", stem, suffix);
+                suffix += 1;
             }
+            metrics_dir.join(name)
+        })
+        .collect()
+}
+
+// Width of the Elo bins in rating_histogram.csv.
+const RATING_HISTOGRAM_BUCKET: u32 = 100;
+
+// How the analysis results are written out.
+struct ExportOptions<'a> {
+    format: OutputFormat,
+    top_n: Option<usize>,
+    sqlite_path: Option<&'a Path>,
+    // analysis_output.csv is the player table (one row per player) instead of
+    // the long Analysis Type/Player/... layout.
+    wide: bool,
+}
+
+impl Default for ExportOptions<'_> {
+    fn default() -> Self {
+        ExportOptions {
+            format: OutputFormat::Csv,
+            top_n: None,
+            sqlite_path: None,
+            wide: false,
         }
+    }
+}
+
+// Every file a run wrote, saved as manifest.json in the output directory.
+// Each entry is added right after the export that wrote the file, so the list
+// only ever names files that were actually produced.
+#[derive(Debug, Default, Serialize)]
+struct Manifest {
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    path: String,
+    // Data rows of a csv file or entries of a json file. None for files that
+    // aren't made of rows, like the graph drawings.
+    rows: Option<usize>,
+    description: String,
+}
 
-        analysis::export_centrality_data(&pagerank_scores, &graph, pr_scores_file)?;
-        analysis::export_centrality_data(&betweenness_centrality, &graph, btw_scores_file)?;
-        analysis::export_centrality_data(&closeness_centrality, &graph, cls_scores_file)?;
-        analysis::export_performance(&performance, player_perf_file)?;
-        analysis::export_in_out_degree_centrality(&in_out_degree_centrality, &graph, in_out_degree_file)?;
-        analysis::export_weighted_centrality(&weighted_betweenness, &weighted_closeness, &graph, weighted_centrality_file)?;
-        analysis::export_mean_mode_metrics(&mean_mode_metrics, mean_mode_metrics_file)?;
+impl Manifest {
+    fn add(&mut self, path: &str, description: &str) -> Result<(), Box<dyn Error>> {
+        self.push(path, description, false)
     }
 
-    output_writer.write_record(&["Analysis Type", "Player", "Score", "Win Rate", "Draws", "Mean Rating Diff", "Game Count"])?;
+    fn add_with_header(&mut self, path: &str, description: &str) -> Result<(), Box<dyn Error>> {
+        self.push(path, description, true)
+    }
 
-    let mut pr_reader = csv::Reader::from_path(pr_scores_file)?;
-    for result in pr_reader.records() {
-        let record = result?;
-        output_writer.write_record(&["PageRank", &record[0], &record[1], "", "", "", ""])?;
+    fn push(&mut self, path: &str, description: &str, has_header: bool) -> Result<(), Box<dyn Error>> {
+        let rows = match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => {
+                let mut reader = csv::ReaderBuilder::new().has_headers(has_header).flexible(true).from_path(path)?;
+                Some(reader.records().count())
+            }
+            Some("json") => {
+                let entries: Vec<serde::de::IgnoredAny> = serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(path)?))?;
+                Some(entries.len())
+            }
+            _ => None,
+        };
+        self.files.push(ManifestEntry {
+            path: path.to_string(),
+            rows,
+            description: description.to_string(),
+        });
+        Ok(())
     }
 
-    let mut btw_reader = csv::Reader::from_path(btw_scores_file)?;
-    for result in btw_reader.records() {
-        let record = result?;
-        output_writer.write_record(&["Betweenness Centrality", &record[0], &record[1], "", "", "", ""])?;
+    fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
     }
+}
+
+// Writes analysis_output.csv to `out_dir` and each input's metric files to
+// its own folder under `out_dir/out`, adding every file to `manifest`.
+fn perform_game_data_analysis(
+    input_files: &[&str],
+    out_dir: &Path,
+    filters: &FilterOptions,
+    graph_options: &GraphOptions,
+    export_options: &ExportOptions,
+    manifest: &mut Manifest,
+) -> Result<(), Box<dyn Error>> {
+    let ExportOptions { format, top_n, sqlite_path, wide } = *export_options;
+    let extension = match format {
+        OutputFormat::Csv => "csv",
+        OutputFormat::Json => "json",
+    };
+    let output_file = out_dir.join("analysis_output.csv");
+    let metrics_dir = out_dir.join("out");
 
-    let mut cls_reader = csv::Reader::from_path(cls_scores_file)?;
-    for result in cls_reader.records() {
-        let record = result?;
-        output_writer.write_record(&["Closeness Centrality", &record[0], &record[1], "", "", "", ""])?;
+    // The combined CSV is assembled from the per-metric CSV files, so it is
+    // only produced in CSV mode.
+    let mut output_writer = match format {
+        OutputFormat::Csv => Some(Writer::from_path(&output_file)?),
+        OutputFormat::Json => None,
+    };
+
+    if let Some(sqlite_path) = sqlite_path {
+        create_sqlite_tables(sqlite_path)?;
     }
 
-    let mut perf_reader = csv::Reader::from_path(player_perf_file)?;
-    for result in perf_reader.records() {
-        let record = result?;
-        output_writer.write_record(&[
-            "Player Performance",
-            &record[0],
-            "",
-            &record[6],
-            &record[3],
-            &record[5],
-            &record[1],
-        ])?;
+    let mut summary = AnalysisSummary::default();
+    let mut exported = Vec::new();
+    let dirs = metrics_dirs(&metrics_dir, input_files);
+    for ((file_analysis, dir), input_file) in analyze_files(input_files, filters, graph_options)?.into_iter().zip(dirs).zip(input_files) {
+        let FileAnalysis {
+            games,
+            graph,
+            pagerank_scores,
+            weighted_pagerank,
+            betweenness_centrality,
+            closeness_centrality,
+            harmonic_centrality,
+            eigenvector_centrality,
+            katz_centrality,
+            recency_pagerank,
+            performance,
+            in_out_degree_centrality,
+            weighted_betweenness,
+            weighted_closeness,
+            mean_mode_metrics,
+            rating_volatility,
+            rating_stats,
+            player_eco_classifications,
+            components,
+            reciprocity,
+            elo_assortativity,
+            upset_rate,
+            dominance_chain,
+            intransitive_triples,
+        } = file_analysis;
+
+        if games.is_empty() {
+            continue;
+        }
+        println!("Player graph for {} has {} connected component(s)", input_file, components.len());
+        println!("Player graph for {} has reciprocity {:.3}", input_file, reciprocity);
+        println!("Elo assortativity for {} is {:.3}", input_file, elo_assortativity);
+        println!("Upset rate for {} is {:.3}", input_file, upset_rate);
+        let chain = &dominance_chain.players;
+        if dominance_chain.depth_limited {
+            println!(
+                "Longest dominance chain of up to {} wins for {} has {} player(s): {} (the graph has cycles, so a longer chain may exist)",
+                DOMINANCE_CHAIN_MAX_DEPTH,
+                input_file,
+                chain.len(),
+                chain.join(" -> ")
+            );
+        } else {
+            println!("Longest dominance chain for {} has {} player(s): {}", input_file, chain.len(), chain.join(" -> "));
+        }
+        println!("Player graph for {} has {} intransitive triple(s)", input_file, intransitive_triples);
+        let upsets = analysis::upset_counts(&games, graph_options.names);
+        let elo_deltas = analysis::elo_performance_delta(&games, graph_options.names);
+        summary.add(&games, &graph, &pagerank_scores, &upsets, &elo_deltas);
+        std::fs::create_dir_all(&dir)?;
+        let files = MetricFiles::new(&dir, extension);
+
+        if let Some(output_writer) = output_writer.as_mut().filter(|_| !wide) {
+            output_writer.write_record(["Player", "ECO", "Count", "", "", "", ""])?;
+            let mut players: Vec<&String> = player_eco_classifications.keys().collect();
+            players.sort();
+            for player in players {
+                let eco_counts = &player_eco_classifications[player];
+                let mut ecos: Vec<&String> = eco_counts.keys().collect();
+                ecos.sort();
+                for eco in ecos {
+                    output_writer.write_record([player.as_str(), eco.as_str(), &eco_counts[eco].to_string(), "", "", "", ""])?;
+                }
+            }
+        }
+
+        export_scores(format, &weighted_pagerank, &graph, &files.weighted_pr_scores, top_n)?;
+        manifest.add(&files.weighted_pr_scores, "PageRank with repeat wins counted per game")?;
+        export_scores(format, &betweenness_centrality, &graph, &files.btw_scores, top_n)?;
+        manifest.add(&files.btw_scores, "betweenness centrality per player")?;
+        export_scores(format, &closeness_centrality, &graph, &files.cls_scores, top_n)?;
+        manifest.add(&files.cls_scores, "closeness centrality per player")?;
+        export_scores(format, &harmonic_centrality, &graph, &files.hrm_scores, top_n)?;
+        manifest.add(&files.hrm_scores, "harmonic centrality per player")?;
+        export_scores(format, &katz_centrality, &graph, &files.katz_scores, top_n)?;
+        manifest.add(&files.katz_scores, "Katz centrality per player")?;
+        if let Some(recency_pagerank) = &recency_pagerank {
+            export_scores(format, recency_pagerank, &graph, &files.recency_pr_scores, top_n)?;
+            manifest.add(&files.recency_pr_scores, "PageRank with recent games weighted more")?;
+        }
+        match format {
+            OutputFormat::Csv => {
+                analysis::export_centrality_data_with_normalized(&pagerank_scores, &graph, &files.pr_scores, top_n)?;
+                analysis::export_eigenvector_centrality(&eigenvector_centrality, &graph, &files.eig_scores, top_n)?;
+                analysis::export_performance(&performance, &files.player_perf, top_n)?;
+            }
+            OutputFormat::Json => {
+                analysis::export_centrality_data_json(&pagerank_scores, &graph, &files.pr_scores, top_n)?;
+                analysis::export_centrality_data_json(&eigenvector_centrality, &graph, &files.eig_scores, top_n)?;
+                analysis::export_performance_json(&performance, &files.player_perf, top_n)?;
+            }
+        }
+        manifest.add(&files.pr_scores, "PageRank per player")?;
+        manifest.add(&files.eig_scores, "eigenvector centrality per player")?;
+        manifest.add(&files.player_perf, "wins, losses, draws and rating changes per player")?;
+        analysis::export_in_out_degree_centrality(&in_out_degree_centrality, &graph, &files.in_out_degree)?;
+        manifest.add(&files.in_out_degree, "in- and out-degree per player")?;
+        analysis::export_weighted_centrality(&weighted_betweenness, &weighted_closeness, &graph, &files.weighted_centrality)?;
+        manifest.add(&files.weighted_centrality, "betweenness and closeness over game-count weighted edges")?;
+        analysis::export_mean_mode_metrics(
+            &mean_mode_metrics,
+            &rating_volatility,
+            &analysis::most_common_opening(&games, graph_options.names),
+            &files.mean_mode_metrics,
+        )?;
+        manifest.add(&files.mean_mode_metrics, "win rate, draws, rating volatility and most common opening per player")?;
+        analysis::export_rating_stats(&rating_stats, &files.rating_stats)?;
+        manifest.add(&files.rating_stats, "mean, median and spread of each player's rating")?;
+        analysis::export_rating_histogram(&analysis::rating_histogram(&games, RATING_HISTOGRAM_BUCKET), &files.rating_histogram)?;
+        manifest.add_with_header(&files.rating_histogram, "ratings binned into 100 point buckets")?;
+        strategy_analysis::export_win_rate_by_opening(&strategy_analysis::win_rate_by_opening(&games, graph_options.names), &files.opening_win_rate)?;
+        manifest.add(&files.opening_win_rate, "win rate per player and opening")?;
+        strategy_analysis::export_avg_moves_by_time_control(&strategy_analysis::avg_moves_by_time_control(&games), &files.avg_moves)?;
+        manifest.add(&files.avg_moves, "average game length per time control")?;
+        strategy_analysis::export_draw_rate_by_time_control(&strategy_analysis::draw_rate_by_time_control(&games), &files.draw_rates)?;
+        manifest.add(&files.draw_rates, "draw rate per time control")?;
+        analysis::export_color_split_performance(&analysis::track_color_split_performance(&games, graph_options.names), &files.color_split)?;
+        manifest.add(&files.color_split, "results per player as white and as black")?;
+        strategy_analysis::export_opening_diversity(&strategy_analysis::opening_diversity(&games, graph_options.names), &files.opening_diversity)?;
+        manifest.add(&files.opening_diversity, "opening diversity per player")?;
+        strategy_analysis::export_eco_outcome_stats(&strategy_analysis::eco_outcome_stats(&games), &files.eco_outcomes)?;
+        manifest.add_with_header(&files.eco_outcomes, "white wins, black wins and draws per ECO code")?;
+        strategy_analysis::export_eco_groups(
+            &strategy_analysis::classify_games_by_eco_family(&games),
+            &strategy_analysis::classify_games_by_eco_range(&games),
+            &strategy_analysis::classify_games_by_eco(&games),
+            &files.eco_groups,
+        )?;
+        manifest.add_with_header(&files.eco_groups, "games per ECO family, range and code")?;
+        strategy_analysis::export_opening_transition_matrix(&strategy_analysis::opening_transition_matrix(&games, graph_options.names), &files.opening_transitions)?;
+        manifest.add_with_header(&files.opening_transitions, "how often a player's next opening followed the previous one")?;
+        moves::export_opening_move_frequency(&moves::opening_move_frequency(&games), &files.first_moves)?;
+        manifest.add(&files.first_moves, "how many games opened with each first move")?;
+        analysis::export_head_to_head(&games, &files.head_to_head)?;
+        manifest.add(&files.head_to_head, "results between each pair of players")?;
+        analysis::export_graph_dot(&graph, &files.graph_dot)?;
+        manifest.add(&files.graph_dot, "player graph in Graphviz format")?;
+        analysis::export_graph_graphml(&graph, &files.graph_graphml)?;
+        manifest.add(&files.graph_graphml, "player graph in GraphML format")?;
+        analysis::export_adjacency_matrix(&graph, &files.adjacency_matrix)?;
+        manifest.add_with_header(&files.adjacency_matrix, "edge weight between every pair of players")?;
+        analysis::export_roster(&graph, &games, graph_options.names, &files.roster)?;
+        manifest.add_with_header(&files.roster, "graph node index and game count per player")?;
+        let player_table = analysis::PlayerTable {
+            graph: &graph,
+            pagerank_scores: &pagerank_scores,
+            betweenness_centrality: &betweenness_centrality,
+            closeness_centrality: &closeness_centrality,
+            performance: &performance,
+            distinct_opponents: &analysis::distinct_opponents_with(&games, graph_options.names),
+            upsets: &upsets,
+            elo_deltas: &elo_deltas,
+            win_streaks: &analysis::longest_win_streak(&games, graph_options.names),
+            strength: &analysis::calculate_strength_centrality(&graph),
+        };
+        analysis::export_wide_player_table(&player_table, &files.player_table)?;
+        manifest.add_with_header(&files.player_table, "every per-player metric in one row")?;
+        analysis::export_connected_components(&components, &files.components)?;
+        manifest.add(&files.components, "connected component of each player")?;
+        if let Some(sqlite_path) = sqlite_path {
+            export_sqlite(sqlite_path, input_file, &performance, &pagerank_scores, &graph)?;
+        }
+        exported.push(files);
+    }
+    // Every input's rows went into the one database.
+    if let Some(sqlite_path) = sqlite_path {
+        manifest.add(&sqlite_path.to_string_lossy(), "performance and PageRank tables of every input")?;
     }
 
-    let mut in_out_degree_reader = csv::Reader::from_path(in_out_degree_file)?;
-    for result in in_out_degree_reader.records() {
-        let record = result?;
-        output_writer.write_record(&["In-Degree", &record[0], &record[1], "", "", "", ""])?;
-        output_writer.write_record(&["Out-Degree", &record[0], &record[1], "", "", "", ""])?;
+    let mut output_writer = match output_writer {
+        Some(output_writer) => output_writer,
+        None => return Ok(()),
+    };
+
+    // The player tables already have a header, which is written once. The
+    // summary rows don't fit the table's columns, so they're left out.
+    if wide {
+        for (i, files) in exported.iter().enumerate() {
+            let mut table_reader = csv::Reader::from_path(&files.player_table)?;
+            if i == 0 {
+                output_writer.write_record(table_reader.headers()?)?;
+            }
+            for result in table_reader.records() {
+                output_writer.write_record(&result?)?;
+            }
+        }
+        output_writer.flush()?;
+        manifest.add_with_header(&output_file.to_string_lossy(), "player tables of every input")?;
+        return Ok(());
     }
 
-    let mut weighted_centrality_reader = csv::Reader::from_path(weighted_centrality_file)?;
-    for result in weighted_centrality_reader.records() {
-        let record = result?;
-        output_writer.write_record(&["Weighted Betweenness", &record[0], &record[1], "", "", "", ""])?;
-        output_writer.write_record(&["Weighted Closeness", &record[0], &record[1], "", "", "", ""])?;
+    // Nothing was exported, so there are no per-metric files to combine.
+    if summary.total_games == 0 {
+        log::warn!("No games were analyzed, {} only has the summary", output_file.display());
+        summary.write(&mut output_writer)?;
+        output_writer.flush()?;
+        manifest.add(&output_file.to_string_lossy(), "summary of the run")?;
+        return Ok(());
     }
 
-    let mut mean_mode_metrics_reader = csv::Reader::from_path(mean_mode_metrics_file)?;
-    for result in mean_mode_metrics_reader.records() {
-        let record = result?;
-        output_writer.write_record(&[
-            "Mean/Mode Metrics",
-            &record[0],
-            "",
-            &record[1],
-            &record[2],
-            &record[3],
-            &record[4],
-        ])?;
+    output_writer.write_record(["Analysis Type", "Player", "Score", "Win Rate", "Draws", "Mean Rating Diff", "Game Count"])?;
+
+    for files in &exported {
+        let mut pr_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.pr_scores)?;
+        for result in pr_reader.records() {
+            let record = result?;
+            output_writer.write_record(["PageRank", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut btw_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.btw_scores)?;
+        for result in btw_reader.records() {
+            let record = result?;
+            output_writer.write_record(["Betweenness Centrality", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut cls_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.cls_scores)?;
+        for result in cls_reader.records() {
+            let record = result?;
+            output_writer.write_record(["Closeness Centrality", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut hrm_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.hrm_scores)?;
+        for result in hrm_reader.records() {
+            let record = result?;
+            output_writer.write_record(["Harmonic Centrality", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut eig_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.eig_scores)?;
+        for result in eig_reader.records() {
+            let record = result?;
+            output_writer.write_record(["Eigenvector Centrality", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut katz_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.katz_scores)?;
+        for result in katz_reader.records() {
+            let record = result?;
+            output_writer.write_record(["Katz Centrality", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut perf_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.player_perf)?;
+        for result in perf_reader.records() {
+            let record = result?;
+            output_writer.write_record([
+                "Player Performance",
+                &record[1],
+                "",
+                &record[7],
+                &record[4],
+                &record[6],
+                &record[2],
+            ])?;
+        }
+
+        let mut in_out_degree_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.in_out_degree)?;
+        for result in in_out_degree_reader.records() {
+            let record = result?;
+            output_writer.write_record(["In-Degree", &record[0], &record[1], "", "", "", ""])?;
+            output_writer.write_record(["Out-Degree", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut weighted_centrality_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.weighted_centrality)?;
+        for result in weighted_centrality_reader.records() {
+            let record = result?;
+            output_writer.write_record(["Weighted Betweenness", &record[0], &record[1], "", "", "", ""])?;
+            output_writer.write_record(["Weighted Closeness", &record[0], &record[1], "", "", "", ""])?;
+        }
+
+        let mut mean_mode_metrics_reader = csv::ReaderBuilder::new().has_headers(false).from_path(&files.mean_mode_metrics)?;
+        for result in mean_mode_metrics_reader.records() {
+            let record = result?;
+            output_writer.write_record([
+                "Mean/Mode Metrics",
+                &record[0],
+                "",
+                &record[1],
+                &record[2],
+                &record[3],
+                &record[4],
+            ])?;
+        }
     }
 
+    summary.write(&mut output_writer)?;
     output_writer.flush()?;
+    manifest.add(&output_file.to_string_lossy(), "every input's metrics in one file, then the summary")?;
 
     Ok(())
 }
@@ -222,7 +1192,7 @@ fn perform_game_data_analysis(input_files: &[&str], output_file: &Path) -> Resul
 
 #[cfg(test)]
 mod tests {
-    use crate::analysis::{Game, build_graph, calculate_in_out_degree_centrality, export_in_out_degree_centrality};
+    use crate::analysis::{Game, build_graph, build_graph_recency_weighted, add_game, calculate_in_out_degree_centrality, calculate_strength_centrality, export_in_out_degree_centrality};
     use std::fs::File;
     use std::io::Read;
     use tempfile::tempdir;
@@ -251,6 +1221,116 @@ mod tests {
         assert_eq!(graph.edge_count(), 2);
     }
 
+    #[test]
+    fn test_build_graph_recency_weighted() {
+        let games: Vec<Game> = [("Player1", "Player2", "1-0"), ("Player1", "Player2", "1-0"), ("Player3", "Player1", "*"), ("Player2", "Player1", "1-0")]
+            .iter()
+            .map(|&(white, black, result)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let graph = build_graph_recency_weighted(&games, 1);
+        let node = |name: &str| graph.node_indices().find(|&i| graph[i] == name).unwrap();
+        let weight = |from: &str, to: &str| graph[graph.find_edge(node(from), node(to)).unwrap()];
+
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        // The two early wins are 3 and 2 games old, the reply is the newest.
+        assert_eq!(weight("Player1", "Player2"), 0.125 + 0.25);
+        assert_eq!(weight("Player2", "Player1"), 1.0);
+        assert!(weight("Player2", "Player1") > weight("Player1", "Player2"));
+
+        let slower = build_graph_recency_weighted(&games, 2);
+        assert_eq!(slower[slower.find_edge(NodeIndex::new(0), NodeIndex::new(1)).unwrap()], 0.5f64.powf(1.5) + 0.5);
+    }
+
+    #[test]
+    fn test_recency_pagerank_favours_recent_wins() {
+        let games: Vec<Game> = [("Player3", "Player1", "1-0"), ("Player3", "Player2", "1-0")]
+            .iter()
+            .map(|&(white, black, result)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let graph = build_graph(&games);
+        let node = |name: &str| graph.node_indices().find(|&i| graph[i] == name).unwrap();
+
+        let plain = calculate_pagerank(&graph);
+        assert!((plain[&node("Player1")] - plain[&node("Player2")]).abs() < 1e-9);
+
+        // Player3 passes on two thirds of their rank for the newer win.
+        let scores = crate::recency_pagerank(&games, &graph, 1, &GraphOptions::default());
+        assert_eq!(scores.len(), 3);
+        assert!(scores[&node("Player2")] > scores[&node("Player1")]);
+        assert!((scores[&node("Player2")] - 0.15 - 2.0 * (scores[&node("Player1")] - 0.15)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_recency_graph_follows_graph_options() {
+        let games: Vec<Game> = [("Player1", "Player2", "1-0"), (" player1 ", "Player2", "1/2-1/2")]
+            .iter()
+            .map(|&(white, black, result)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let options = GraphOptions { names: NameNormalization::TrimAndCaseFold, draws: DrawPolicy::Skip, ..Default::default() };
+
+        let graph = build_graph_with(&games, &options);
+        let recency_graph = crate::analysis::build_graph_recency_weighted_with(&games, 1, &options);
+        assert_eq!(recency_graph.node_weights().collect::<Vec<_>>(), graph.node_weights().collect::<Vec<_>>());
+        assert_eq!(recency_graph.edge_count(), 1);
+        assert_eq!(recency_graph[recency_graph.find_edge(NodeIndex::new(0), NodeIndex::new(1)).unwrap()], 0.5);
+
+        // Both spellings of Player1 land on the one node, so every node scores.
+        let scores = crate::recency_pagerank(&games, &graph, 1, &options);
+        assert_eq!(scores.len(), graph.node_count());
+
+        let undirected = GraphOptions { mode: GraphMode::Undirected, ..options };
+        assert_eq!(crate::analysis::build_graph_recency_weighted_with(&games, 1, &undirected).edge_count(), 2);
+    }
+
+    #[test]
+    fn test_add_game_matches_build_graph() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "1/2-1/2".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let mut graph = petgraph::graph::DiGraph::new();
+        let mut player_indices = std::collections::HashMap::new();
+        for game in &games {
+            add_game(&mut graph, &mut player_indices, game, &GraphOptions::default());
+        }
+
+        let built = build_graph(&games);
+        assert_eq!(graph.node_count(), built.node_count());
+        assert_eq!(graph.edge_count(), built.edge_count());
+        assert_eq!(graph_diff(&built, &graph), GraphDiff::default());
+        assert_eq!(player_indices.len(), 2);
+    }
+
     #[test]
     fn test_calculate_pagerank() {
         let games = vec![
@@ -276,6 +1356,70 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_calculate_weighted_pagerank() {
+        // Player1 beat Player2 ten times but Player3 only once. Unweighted,
+        // both losers get the same share of Player1's rank; weighted, Player2
+        // gets ten times as much of it.
+        let mut graph = DiGraph::<String, u32>::new();
+        let player1 = graph.add_node("Player1".to_string());
+        let player2 = graph.add_node("Player2".to_string());
+        let player3 = graph.add_node("Player3".to_string());
+        graph.add_edge(player1, player2, 10);
+        graph.add_edge(player1, player3, 1);
+
+        let unweighted = calculate_pagerank(&graph);
+        assert!((unweighted[&player2] - unweighted[&player3]).abs() < 1e-9);
+
+        let weighted = calculate_weighted_pagerank(&graph);
+        assert_eq!(weighted.len(), 3);
+        assert!(weighted[&player2] > weighted[&player3]);
+        assert!((weighted[&player2] - (0.15 + 0.85 * 10.0 / 11.0 * 0.15)).abs() < 1e-9);
+        assert!((weighted[&player3] - (0.15 + 0.85 * 1.0 / 11.0 * 0.15)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_pagerank_damping() {
+        // Three players each beat Hub once, while Player4 and Player5 beat each
+        // other. Fully damped, rank only flows along edges and drains out of
+        // the leaves, so Hub ends at zero; at 0.5 the baseline every leaf
+        // passes on lifts Hub above the pair.
+        let mut graph = DiGraph::<String, u32>::new();
+        let hub = graph.add_node("Hub".to_string());
+        for leaf in ["Player1", "Player2", "Player3"] {
+            let leaf = graph.add_node(leaf.to_string());
+            graph.add_edge(leaf, hub, 1);
+        }
+        let player4 = graph.add_node("Player4".to_string());
+        let player5 = graph.add_node("Player5".to_string());
+        graph.add_edge(player4, player5, 1);
+        graph.add_edge(player5, player4, 1);
+
+        let undamped = calculate_pagerank_with(&graph, 1.0, 100);
+        assert!(undamped[&hub].abs() < 1e-9);
+        assert!((undamped[&player4] - 1.0).abs() < 1e-9);
+
+        let damped = calculate_pagerank_with(&graph, 0.5, 100);
+        assert!((damped[&hub] - 1.25).abs() < 1e-9);
+        assert!((damped[&player4] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_pagerank_large_graph() {
+        // Scores are indexed straight by NodeIndex, so a 10k-player chain is a
+        // handful of linear passes and every node comes back with a score.
+        let mut graph = DiGraph::<String, u32>::new();
+        let nodes: Vec<NodeIndex> = (0..10_000).map(|i| graph.add_node(format!("Player{}", i))).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(pair[0], pair[1], 1);
+        }
+
+        let pagerank_scores = calculate_pagerank(&graph);
+        assert_eq!(pagerank_scores.len(), 10_000);
+        assert!(pagerank_scores.values().all(|score| score.is_finite()));
+        assert!((pagerank_scores[&nodes[0]] - (1.0 - crate::analysis::DEFAULT_PAGERANK_DAMPING)).abs() < 1e-9);
+    }
+
     #[test]
     fn test_calculate_in_out_degree_centrality() {
         let games = vec![
@@ -306,15 +1450,54 @@ mod tests {
         let in_out_degree_centrality = calculate_in_out_degree_centrality(&graph);
         assert_eq!(in_out_degree_centrality.len(), 3);
 
-        assert_eq!(in_out_degree_centrality[&graph.node_indices().nth(0).unwrap()], (1, 1));
-        assert_eq!(in_out_degree_centrality[&graph.node_indices().nth(1).unwrap()], (1, 1));
-        assert_eq!(in_out_degree_centrality[&graph.node_indices().nth(2).unwrap()], (1, 1));
+        // Player1 and Player3 beat Player2 and drew each other, which adds an
+        // edge both ways.
+        assert_eq!(in_out_degree_centrality[&graph.node_indices().next().unwrap()], (1, 2));
+        assert_eq!(in_out_degree_centrality[&graph.node_indices().nth(1).unwrap()], (2, 0));
+        assert_eq!(in_out_degree_centrality[&graph.node_indices().nth(2).unwrap()], (1, 2));
+    }
+
+    #[test]
+    fn test_calculate_strength_centrality() {
+        let games: Vec<Game> = (1..=3)
+            .map(|id| Game {
+                game_id: id.to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            })
+            .chain(std::iter::once(Game {
+                game_id: "4".to_string(),
+                white: "Player3".to_string(),
+                black: "Player1".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            }))
+            .collect();
+
+        let graph = build_graph(&games);
+        let strength = calculate_strength_centrality(&graph);
+        let degree = calculate_in_out_degree_centrality(&graph);
+        let player1 = graph.node_indices().find(|&node| graph[node] == "Player1").unwrap();
+        let player2 = graph.node_indices().find(|&node| graph[node] == "Player2").unwrap();
+
+        assert_eq!(strength[&player1], (1, 3));
+        assert_eq!(degree[&player1], (1, 1));
+        assert_eq!(strength[&player2], (3, 0));
     }
 
-    use crate::analysis::calculate_pagerank;
+    use crate::{scan_csv_files, combined_rows, analyze_file, analyze_files, AnalysisSummary, init_logging, dry_run};
+    use crate::filters::FilterOptions;
+    use crate::analysis::{GraphMode, GraphOptions, build_graph_with, calculate_betweenness_centrality, calculate_closeness_centrality, calculate_weighted_centrality, weighted_distances, calculate_katz_centrality, build_graph_from_dataframe, export_performance, export_centrality_data, PlayerPerformance, NameNormalization, normalize_name, track_player_performance_with, merge_performance_maps, DrawPolicy, EdgeMode, normalize_scores, export_centrality_data_with_normalized, percentile_ranks};
+    use petgraph::graph::{DiGraph, NodeIndex};
+    use std::collections::HashMap;
+    use crate::analysis::{calculate_pagerank, calculate_pagerank_with, calculate_mean_mode, calculate_weighted_pagerank, calculate_eigenvector_centrality, read_games_from_dataframe, read_games_from_ndjson, head_to_head, head_to_head_records, export_graph_dot, export_graph_graphml, export_adjacency_matrix, export_roster, export_wide_player_table, PlayerTable, distinct_opponents_with, longest_win_streak, most_common_opening, most_active_players, calculate_harmonic_centrality, connected_components, calculate_rating_stats, rating_volatility, upset_rate, upset_counts, overall_draw_rate, find_result_rating_inconsistencies, graph_density, detect_result_scheme, normalize_results, ResultScheme, GameResult, elo_performance_delta, elo_assortativity, rating_histogram, export_rating_histogram, graph_reciprocity, longest_dominance_chain, DominanceChain, find_intransitive_triples, graph_diff, export_graph_diff, GraphDiff, shortest_path_between, top_player, players_without_decisive_games, track_color_split_performance, export_color_split_performance};
+    use crate::error::AnalysisError;
+    use polars::prelude::*;
 
     #[test]
-    fn test_export_in_out_degree_centrality() {
+    fn test_calculate_eigenvector_centrality() {
         let games = vec![
             Game {
                 game_id: "1".to_string(),
@@ -330,9 +1513,46 @@ mod tests {
                 result: "0-1".to_string(),
                 ..Default::default()
             },
-        ];
-    
-        let graph = build_graph(&games);
+            Game {
+                game_id: "3".to_string(),
+                white: "Player3".to_string(),
+                black: "Player1".to_string(),
+                result: "1/2-1/2".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let graph = build_graph(&games);
+        let eigenvector_scores = calculate_eigenvector_centrality(&graph).unwrap();
+        assert_eq!(eigenvector_scores.len(), 3);
+
+        // Player1 and Player3 drew, so they pass score to each other, and both
+        // beat Player2, who collects from the two of them.
+        let score = |index: usize| eigenvector_scores[&NodeIndex::new(index)];
+        assert!((score(0) - score(2)).abs() < 1e-6);
+        assert!((score(1) - 2.0 * score(0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_export_in_out_degree_centrality() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player3".to_string(),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+        ];
+    
+        let graph = build_graph(&games);
         let in_out_degree_centrality = calculate_in_out_degree_centrality(&graph);
     
         let dir = tempdir().unwrap();
@@ -347,11 +1567,2065 @@ mod tests {
         actual_lines.sort();
         let actual_contents = actual_lines.join("\n");
     
-        let expected_lines: Vec<&str> = ["Player1,0,1", "Player2,1,1", "Player3,1,0"].to_vec();
+        let expected_lines: Vec<&str> = ["Player1,0,1", "Player2,2,0", "Player3,0,1"].to_vec();
         let expected_contents = expected_lines.join("\n");
     
         assert_eq!(actual_contents, expected_contents);
     }
-    
 
+    #[test]
+    fn test_read_games_missing_column() {
+        let df = df!(
+            "GameID" => &["1"],
+            "White" => &["Player1"],
+            "Black" => &["Player2"]
+        )
+        .unwrap();
+
+        match read_games_from_dataframe(&df) {
+            Err(AnalysisError::MissingColumn(column)) => assert_eq!(column, "Result"),
+            other => panic!("expected MissingColumn, got {:?}", other.map(|games| games.len())),
+        }
+    }
+
+    #[test]
+    fn test_read_games_missing_optional_column() {
+        let df = df!(
+            "GameID" => &["1", "2"],
+            "Event" => &["Rated Blitz game", "Rated Blitz game"],
+            "White" => &["Player1", "Player2"],
+            "WhiteElo" => &[1500i64, 1600],
+            "WhiteRatingDiff" => &[5.0, -4.0],
+            "White_tosViolation" => &[false, false],
+            "White_playTime_total" => &[1000.0, 2000.0],
+            "Black" => &["Player2", "Player1"],
+            "BlackElo" => &[1600i64, 1500],
+            "BlackRatingDiff" => &[-5.0, 4.0],
+            "Black_tosViolation" => &[false, false],
+            "Black_playTime_total" => &[3000.0, 4000.0],
+            "Black_count_all" => &[10.0, 20.0],
+            "Moves" => &["e4 e5", "d4 d5"],
+            "TotalMoves" => &[2i64, 2],
+            "ECO" => &["C20", "D00"],
+            "Opening" => &["King's Pawn Game", "Queen's Pawn Game"],
+            "TimeControl" => &["300+0", "300+0"],
+            "Result" => &["Normal", "Normal"]
+        )
+        .unwrap();
+
+        let games = read_games_from_dataframe(&df).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].white, "Player1");
+        assert_eq!(games[0].white_elo, Some(1500));
+        assert_eq!(games[0].white_count_all, None);
+        assert_eq!(games[1].black_count_all, Some(20));
+    }
+
+    #[test]
+    fn test_head_to_head() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "Normal".to_string(),
+                white_rating_diff: Some(-6.0),
+                black_rating_diff: Some(6.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "1/2-1/2".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(head_to_head(&games, "Player1", "Player2"), (2, 0, 1));
+        assert_eq!(head_to_head(&games, "Player2", "Player1"), (0, 2, 1));
+        assert_eq!(head_to_head(&games, "Player1", "Player3"), (0, 0, 0));
+
+        let records = head_to_head_records(&games);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[&("Player1".to_string(), "Player2".to_string())], (2, 0, 1));
+    }
+
+    #[test]
+    fn test_export_graph_dot() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let graph = build_graph(&games);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("player_graph.dot");
+        export_graph_dot(&graph, file_path.to_str().unwrap()).unwrap();
+
+        let mut contents = String::new();
+        File::open(file_path).unwrap().read_to_string(&mut contents).unwrap();
+
+        assert!(contents.contains("digraph"));
+        assert!(contents.contains("label = \"Player1\""));
+        assert!(contents.contains("label = \"Player2\""));
+        assert!(contents.contains("label = \"1\""));
+
+        let escaped = build_graph(&[Game {
+            white: r"Back\slash".to_string(),
+            black: r#""Quoted\""#.to_string(),
+            result: "1-0".to_string(),
+            ..Default::default()
+        }]);
+        let escaped_path = dir.path().join("escaped.dot");
+        export_graph_dot(&escaped, escaped_path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&escaped_path).unwrap();
+        assert!(contents.contains(r#"label = "Back\\slash""#));
+        assert!(contents.contains(r#"label = "\"Quoted\\\"""#));
+    }
+
+    #[test]
+    fn test_export_wide_player_table() {
+        let games: Vec<Game> = [("Player1", "Player2", "1-0", 8.0, -8.0), ("Player2", "Player1", "0-1", -6.0, 6.0)]
+            .iter()
+            .map(|&(white, black, result, white_rating_diff, black_rating_diff)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                white_rating_diff: Some(white_rating_diff),
+                black_rating_diff: Some(black_rating_diff),
+                ..Default::default()
+            })
+            .collect();
+        let graph = build_graph(&games);
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("player_table.csv");
+        let table = PlayerTable {
+            graph: &graph,
+            pagerank_scores: &calculate_pagerank(&graph),
+            betweenness_centrality: &calculate_betweenness_centrality(&graph, None, 0),
+            closeness_centrality: &calculate_closeness_centrality(&graph),
+            performance: &track_player_performance_with(&games, NameNormalization::Exact),
+            distinct_opponents: &distinct_opponents_with(&games, NameNormalization::Exact),
+            upsets: &HashMap::from([("Player1".to_string(), 1)]),
+            elo_deltas: &HashMap::from([("Player1".to_string(), 0.75)]),
+            win_streaks: &longest_win_streak(&games, NameNormalization::Exact),
+            strength: &calculate_strength_centrality(&graph),
+        };
+        export_wide_player_table(&table, file_path.to_str().unwrap()).unwrap();
+
+        let mut reader = csv::Reader::from_path(&file_path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["player", "pagerank", "betweenness", "closeness", "win_rate", "games", "mean_rating_diff", "distinct_opponents", "upsets", "elo_delta", "longest_win_streak", "strength_in", "strength_out"]
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(|row| row.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        let player1 = &rows[0];
+        assert_eq!(&player1[0], "Player1");
+        assert!(player1.iter().all(|field| !field.is_empty()));
+        assert_eq!(&player1[4], "1.0");
+        assert_eq!(&player1[5], "2");
+        assert_eq!(player1[6].parse::<f64>().unwrap(), 7.0);
+        assert_eq!(&player1[7], "1");
+        assert_eq!(&player1[8], "1");
+        assert_eq!(&rows[1][8], "0");
+        assert_eq!(&player1[9], "0.75");
+        assert_eq!(&rows[1][9], "");
+        assert_eq!(&player1[10], "2");
+        assert_eq!(&rows[1][10], "0");
+        assert_eq!((&player1[11], &player1[12]), ("0", "2"));
+        assert_eq!((&rows[1][11], &rows[1][12]), ("2", "0"));
+    }
+
+    #[test]
+    fn test_export_adjacency_matrix() {
+        let mut graph = DiGraph::<String, u32>::new();
+        let player1 = graph.add_node("Player1".to_string());
+        let player2 = graph.add_node("Player2".to_string());
+        graph.add_edge(player1, player2, 3);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("adjacency.csv");
+        export_adjacency_matrix(&graph, file_path.to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            ",Player1,Player2\nPlayer1,0,3\nPlayer2,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_export_roster() {
+        let mut games = decisive_games(&[("Player1", "Player2"), ("Player2", "Player3"), ("Player1", "Player3")]);
+        games.extend(decisive_games(&[("Player1", "Player2")]));
+        let graph = build_graph(&games);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("roster.csv");
+        export_roster(&graph, &games, NameNormalization::Exact, file_path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&file_path).unwrap();
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len() - 1, graph.node_count());
+        assert_eq!(lines, vec!["node_index,player,games_played", "0,Player1,3", "1,Player2,3", "2,Player3,2"]);
+
+        // Every spelling of a merged player counts towards their node.
+        let games = decisive_games(&[("Alice", "Carol"), (" ALICE ", "Bob"), ("carol", "alice")]);
+        let normalization = NameNormalization::TrimAndCaseFold;
+        let graph = build_graph_with(&games, &GraphOptions { names: normalization, ..Default::default() });
+        export_roster(&graph, &games, normalization, file_path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), vec!["node_index,player,games_played", "0,Alice,3", "1,Carol,2", "2,Bob,1"]);
+    }
+
+    // Just enough of an XML parser for the GraphML test: every tag must be
+    // closed in order and every element sits inside the single root.
+    fn assert_well_formed_xml(xml: &str) {
+        let mut open_tags: Vec<&str> = Vec::new();
+        let mut roots = 0;
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            assert!(!rest[..start].contains('>'), "stray '>' in {:?}", &rest[..start]);
+            let end = rest[start..].find('>').expect("unclosed tag") + start;
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('?') {
+                assert!(tag.ends_with('?'));
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open_tags.pop(), Some(name.trim()), "mismatched closing tag");
+                continue;
+            }
+            let name = tag.split_whitespace().next().unwrap_or_default().trim_end_matches('/');
+            assert!(!name.is_empty(), "empty tag");
+            assert_eq!(tag.matches('"').count() % 2, 0, "unbalanced quotes in <{}>", tag);
+            if open_tags.is_empty() {
+                roots += 1;
+            }
+            if !tag.ends_with('/') {
+                open_tags.push(name);
+            }
+        }
+        assert!(open_tags.is_empty(), "unclosed tags: {:?}", open_tags);
+        assert_eq!(roots, 1);
+    }
+
+    #[test]
+    fn test_export_graph_graphml() {
+        let mut games = decisive_games(&[("Player1", "Player2"), ("Player1", "Player2")]);
+        games.extend(decisive_games(&[("Player <3>", "Player1")]));
+        let graph = build_graph(&games);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("player_graph.graphml");
+        export_graph_graphml(&graph, file_path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&file_path).unwrap();
+
+        assert_well_formed_xml(&contents);
+        assert!(contents.contains(r#"<node id="n0"><data key="name">Player1</data></node>"#));
+        assert!(contents.contains(r#"<data key="name">Player2</data>"#));
+        assert!(contents.contains(r#"<data key="name">Player &lt;3&gt;</data>"#));
+        assert!(contents.contains(r#"<edge id="e0" source="n0" target="n1"><data key="weight">2</data></edge>"#));
+    }
+
+    #[test]
+    fn test_calculate_harmonic_centrality_disconnected() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player3".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player4".to_string(),
+                black: "Player5".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let graph = build_graph(&games);
+        let harmonic_scores = calculate_harmonic_centrality(&graph);
+        assert_eq!(harmonic_scores.len(), 5);
+        assert!(harmonic_scores.values().all(|score| score.is_finite()));
+
+        let score_of = |name: &str| harmonic_scores[&graph.node_indices().find(|&i| graph[i] == name).unwrap()];
+        assert_eq!(score_of("Player1"), 0.0);
+        assert_eq!(score_of("Player2"), 1.0);
+        assert_eq!(score_of("Player3"), 1.5);
+        assert_eq!(score_of("Player5"), 1.0);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player3".to_string(),
+                black: "Player4".to_string(),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let graph = build_graph(&games);
+        let components = connected_components(&graph);
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0], vec!["Player1".to_string(), "Player2".to_string()]);
+        assert_eq!(components[1], vec!["Player3".to_string(), "Player4".to_string()]);
+    }
+
+    #[test]
+    fn test_graph_density() {
+        let graph = build_graph(&decisive_games(&[("Player1", "Player2"), ("Player2", "Player3")]));
+        assert_eq!(graph.node_count(), 3);
+        assert!((graph_density(&graph) - 2.0 / 6.0).abs() < 1e-12);
+
+        let single = build_graph(&decisive_games(&[("Player1", "Player1")]));
+        assert_eq!(graph_density(&single), 0.0);
+        assert_eq!(graph_density(&DiGraph::new()), 0.0);
+    }
+
+    #[test]
+    fn test_graph_reciprocity() {
+        let games: Vec<Game> = [("Player1", "Player2", "1-0"), ("Player1", "Player2", "1-0"), ("Player1", "Player2", "0-1"), ("Player3", "Player4", "1-0")]
+            .iter()
+            .enumerate()
+            .map(|(index, &(white, black, result))| Game {
+                game_id: (index + 1).to_string(),
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let graph = build_graph(&games);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph_reciprocity(&graph), 0.5);
+        assert_eq!(graph_reciprocity(&DiGraph::new()), 0.0);
+    }
+
+    fn decisive_games(results: &[(&str, &str)]) -> Vec<Game> {
+        results
+            .iter()
+            .enumerate()
+            .map(|(index, &(winner, loser))| Game {
+                game_id: (index + 1).to_string(),
+                white: winner.to_string(),
+                black: loser.to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_longest_dominance_chain_acyclic() {
+        // Player1 also beat Player3 directly, which is a shortcut, not a longer chain.
+        let games = decisive_games(&[("Player3", "Player4"), ("Player1", "Player2"), ("Player2", "Player3"), ("Player1", "Player3")]);
+        let graph = build_graph(&games);
+
+        let chain = longest_dominance_chain(&graph, 2);
+        assert_eq!(chain.players, vec!["Player1", "Player2", "Player3", "Player4"]);
+        assert!(!chain.depth_limited);
+        assert_eq!(longest_dominance_chain(&DiGraph::new(), 2), DominanceChain::default());
+    }
+
+    #[test]
+    fn test_longest_dominance_chain_with_cycle_respects_depth() {
+        let games = decisive_games(&[("Player1", "Player2"), ("Player2", "Player3"), ("Player3", "Player1"), ("Player3", "Player4")]);
+        let graph = build_graph(&games);
+
+        let chain = longest_dominance_chain(&graph, 3);
+        assert_eq!(chain.players, vec!["Player1", "Player2", "Player3", "Player4"]);
+        assert!(chain.depth_limited);
+        assert_eq!(longest_dominance_chain(&graph, 1).players.len(), 2);
+        // With room to spare the cyclic search sees every chain, so it's exact.
+        let chain = longest_dominance_chain(&graph, 5);
+        assert_eq!(chain.players.len(), 4);
+        assert!(!chain.depth_limited);
+    }
+
+    #[test]
+    fn test_merge_performance_maps() {
+        let record = |won: u32, lost: u32, drawn: u32, rating_change: f32| PlayerPerformance {
+            games_played: won + lost + drawn,
+            games_won: won,
+            games_lost: lost,
+            games_drawn: drawn,
+            total_rating_change: rating_change,
+            win_rate: won as f64 / (won + lost + drawn) as f64,
+        };
+        let first = HashMap::from([("Player1".to_string(), record(1, 1, 0, 2.0)), ("Player2".to_string(), record(0, 1, 0, -5.0))]);
+        let second = HashMap::from([("Player1".to_string(), record(2, 0, 0, 8.0)), ("Player3".to_string(), record(0, 0, 1, 0.0))]);
+
+        let merged = merge_performance_maps(first, second);
+
+        assert_eq!(merged.len(), 3);
+        let player1 = &merged["Player1"];
+        assert_eq!((player1.games_played, player1.games_won, player1.games_lost), (4, 3, 1));
+        assert_eq!(player1.total_rating_change, 10.0);
+        assert_eq!(player1.win_rate, 0.75);
+        assert_eq!(merged["Player2"].games_lost, 1);
+        assert_eq!(merged["Player3"].games_drawn, 1);
+    }
+
+    #[test]
+    fn test_track_player_performance_counts_both_colors() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let performance = track_player_performance_with(&games, NameNormalization::Exact);
+        assert_eq!(performance["Player1"].games_played, 2);
+        assert_eq!(performance["Player1"].win_rate, 0.5);
+    }
+
+    #[test]
+    fn test_distinct_opponents() {
+        let games: Vec<Game> = [("Player1", "Player2"), ("Player2", "Player1"), ("Player1", "Player3")]
+            .iter()
+            .map(|&(white, black)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let opponents = distinct_opponents_with(&games, NameNormalization::Exact);
+        assert_eq!(opponents["Player1"], 2);
+        assert_eq!(opponents["Player2"], 1);
+        assert_eq!(opponents["Player3"], 1);
+    }
+
+    #[test]
+    fn test_most_active_players() {
+        let games: Vec<Game> = [("Player2", "Player1"), ("Player3", "Player2"), ("Player1", "Player4"), ("Player3", "Player5")]
+            .iter()
+            .map(|&(white, black)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        assert_eq!(
+            most_active_players(&games, 3),
+            vec![("Player1".to_string(), 2), ("Player2".to_string(), 2), ("Player3".to_string(), 2)]
+        );
+        assert_eq!(most_active_players(&games, 10).len(), 5);
+        assert!(most_active_players(&games, 0).is_empty());
+    }
+
+    #[test]
+    fn test_longest_win_streak() {
+        let games: Vec<Game> = [
+            ("Player1", "Player2", "1-0"),
+            ("Player3", "Player1", "0-1"),
+            ("Player1", "Player2", "1-0"),
+            ("Player2", "Player1", "1-0"),
+            ("Player1", "Player3", "1-0"),
+        ]
+        .iter()
+        .map(|&(white, black, result)| Game {
+            white: white.to_string(),
+            black: black.to_string(),
+            result: result.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+        let streaks = longest_win_streak(&games, NameNormalization::Exact);
+        assert_eq!(streaks["Player1"], 3);
+        assert_eq!(streaks["Player2"], 1);
+        assert_eq!(streaks["Player3"], 0);
+    }
+
+    #[test]
+    fn test_calculate_mean_mode_counts_both_colors() {
+        let games: Vec<Game> = [("Player1", "Player2", "1-0", 8.0, -8.0), ("Player2", "Player1", "1/2-1/2", 1.0, -1.0), ("Player3", "Player1", "1-0", 6.0, -6.0)]
+            .iter()
+            .map(|&(white, black, result, white_rating_diff, black_rating_diff)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                white_rating_diff: Some(white_rating_diff),
+                black_rating_diff: Some(black_rating_diff),
+                ..Default::default()
+            })
+            .collect();
+
+        let metrics = calculate_mean_mode(&games, NameNormalization::Exact);
+        // Player1 won as white, drew and lost as black: all three games count.
+        let (win_rate, draws, mean_rating_diff, game_count) = metrics["Player1"];
+        assert_eq!(game_count, 3);
+        assert!((win_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(draws, 0.5);
+        assert!((mean_rating_diff - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metrics["Player2"], (0.0, 0.5, -3.5, 2));
+        assert_eq!(metrics["Player3"], (1.0, 0.0, 6.0, 1));
+    }
+
+    #[test]
+    fn test_most_common_opening() {
+        let games: Vec<Game> = [
+            ("Player1", "Player2", "C60"),
+            ("Player3", "Player1", "B20"),
+            ("Player1", "Player3", "C60"),
+            ("Player2", "Player1", "C60"),
+            ("Player2", "Player3", ""),
+        ]
+        .iter()
+        .map(|&(white, black, eco)| Game {
+            white: white.to_string(),
+            black: black.to_string(),
+            eco: eco.to_string(),
+            result: "1-0".to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+        let openings = most_common_opening(&games, NameNormalization::Exact);
+        assert_eq!(openings["Player1"], "C60");
+        assert_eq!(openings["Player2"], "C60");
+        // One B20 and one C60, so the tie goes to B20.
+        assert_eq!(openings["Player3"], "B20");
+    }
+
+    #[test]
+    fn test_track_color_split_performance() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player3".to_string(),
+                black: "Player1".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let performance = track_color_split_performance(&games, NameNormalization::Exact);
+        let player1 = &performance["Player1"];
+        assert_eq!((player1.white.games_played, player1.white.games_won), (1, 1));
+        assert_eq!(player1.white.win_rate, 1.0);
+        assert_eq!((player1.black.games_played, player1.black.games_lost), (2, 2));
+        assert_eq!(player1.black.win_rate, 0.0);
+        assert_eq!(performance["Player3"].black.games_played, 0);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("color_split.csv");
+        export_color_split_performance(&performance, file_path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(contents.lines().next(), Some("Player1,1,1.0,2,0.0"));
+    }
+
+    #[test]
+    fn test_detect_result_scheme() {
+        let with_results = |results: &[&str]| -> Vec<Game> {
+            results
+                .iter()
+                .enumerate()
+                .map(|(index, &result)| Game {
+                    game_id: (index + 1).to_string(),
+                    white: "Player1".to_string(),
+                    black: "Player2".to_string(),
+                    result: result.to_string(),
+                    white_rating_diff: Some(-6.0),
+                    black_rating_diff: Some(6.0),
+                    ..Default::default()
+                })
+                .collect()
+        };
+
+        assert_eq!(detect_result_scheme(&with_results(&["1-0", "1/2-1/2", "0-1", "*"])), ResultScheme::Pgn);
+        assert_eq!(detect_result_scheme(&with_results(&["Normal", "Time forfeit", "Abandoned"])), ResultScheme::Termination);
+        assert_eq!(detect_result_scheme(&with_results(&["White", "Draw", "", "Black"])), ResultScheme::Winner);
+        assert_eq!(detect_result_scheme(&with_results(&["1", "0.5", "0.0"])), ResultScheme::Numeric);
+        assert_eq!(detect_result_scheme(&[]), ResultScheme::Pgn);
+
+        let mut games = with_results(&["White", "Black", "Draw", "unknown"]);
+        normalize_results(&mut games, ResultScheme::Winner);
+        let results: Vec<&str> = games.iter().map(|game| game.result.as_str()).collect();
+        assert_eq!(results, vec!["1-0", "0-1", "1/2-1/2", "unknown"]);
+
+        let mut games = with_results(&["Normal"]);
+        normalize_results(&mut games, ResultScheme::Termination);
+        assert_eq!(GameResult::from_game(&games[0]), Some(GameResult::BlackWin));
+        assert_eq!(games[0].result, "0-1");
+    }
+
+    #[test]
+    fn test_read_games_numeric_results() {
+        let df = df!(
+            "White" => &["Player1", "Player2", "Player3"],
+            "Black" => &["Player2", "Player3", "Player1"],
+            "Result" => &[1.0, 0.5, 0.0]
+        )
+        .unwrap();
+
+        let mut games = read_games_from_dataframe(&df).unwrap();
+        let scheme = detect_result_scheme(&games);
+        assert_eq!(scheme, ResultScheme::Numeric);
+        normalize_results(&mut games, scheme);
+        let results: Vec<&str> = games.iter().map(|game| game.result.as_str()).collect();
+        assert_eq!(results, vec!["1-0", "1/2-1/2", "0-1"]);
+    }
+
+    #[test]
+    fn test_players_without_decisive_games() {
+        let games: Vec<Game> = [("Player1", "Player2", "1/2-1/2"), ("Player1", "Player3", "1-0"), ("Player4", "Player2", "1/2-1/2"), ("Player5", "Player4", "*")]
+            .iter()
+            .enumerate()
+            .map(|(index, &(white, black, result))| Game {
+                game_id: (index + 1).to_string(),
+                white: white.to_string(),
+                black: black.to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        assert_eq!(players_without_decisive_games(&games), vec!["Player2", "Player4", "Player5"]);
+        assert!(players_without_decisive_games(&decisive_games(&[("Player1", "Player2")])).is_empty());
+    }
+
+    #[test]
+    fn test_find_result_rating_inconsistencies() {
+        let games: Vec<Game> = [
+            ("1", "1-0", Some(8.0), Some(-8.0)),
+            ("2", "1-0", Some(-7.0), Some(7.0)),
+            ("3", "0-1", Some(-5.0), Some(5.0)),
+            ("4", "1/2-1/2", Some(-1.0), Some(1.0)),
+            ("5", "0-1", Some(6.0), None),
+        ]
+        .iter()
+        .map(|&(game_id, result, white_rating_diff, black_rating_diff)| Game {
+            game_id: game_id.to_string(),
+            white: "Player1".to_string(),
+            black: "Player2".to_string(),
+            result: result.to_string(),
+            white_rating_diff,
+            black_rating_diff,
+            ..Default::default()
+        })
+        .collect();
+
+        assert_eq!(find_result_rating_inconsistencies(&games), vec!["2".to_string()]);
+    }
+
+    #[test]
+    fn test_overall_draw_rate() {
+        let games: Vec<Game> = ["1-0", "1/2-1/2", "0-1", "1-0"]
+            .iter()
+            .map(|&result| Game {
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        assert_eq!(overall_draw_rate(&games), 0.25);
+        assert_eq!(overall_draw_rate(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_upset_rate() {
+        let game = |game_id: &str, white: &str, white_elo: Option<u32>, black: &str, black_elo: Option<u32>, result: &str| Game {
+            game_id: game_id.to_string(),
+            white: white.to_string(),
+            white_elo,
+            black: black.to_string(),
+            black_elo,
+            result: result.to_string(),
+            ..Default::default()
+        };
+        let games = vec![
+            // Upset: the 1500 beats the 2000.
+            game("1", "Player1", Some(1500), "Player2", Some(2000), "1-0"),
+            // Expected result.
+            game("2", "Player3", Some(1800), "Player2", Some(2000), "0-1"),
+            // Skipped: draw, missing Elo, equal ratings.
+            game("3", "Player1", Some(1500), "Player3", Some(1800), "1/2-1/2"),
+            game("4", "Player1", None, "Player2", Some(2000), "1-0"),
+            game("5", "Player3", Some(1800), "Player4", Some(1800), "1-0"),
+        ];
+
+        assert_eq!(upset_rate(&games), 0.5);
+        let counts = upset_counts(&games, NameNormalization::Exact);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts["Player1"], 1);
+        assert_eq!(upset_rate(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_elo_performance_delta() {
+        // A 400-point gap gives the stronger player an expected score of 10/11.
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                white_elo: Some(2000),
+                black: "Player2".to_string(),
+                black_elo: Some(1600),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player1".to_string(),
+                white_elo: Some(2000),
+                black: "Player3".to_string(),
+                black_elo: None,
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let deltas = elo_performance_delta(&games, NameNormalization::Exact);
+        assert_eq!(deltas.len(), 2);
+        assert!((deltas["Player1"] + 10.0 / 11.0).abs() < 1e-9);
+        assert!((deltas["Player2"] - 10.0 / 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rating_histogram() {
+        let games: Vec<Game> = [(Some(1450), Some(1599)), (Some(1600), None), (None, Some(1799)), (Some(2010), Some(1400))]
+            .iter()
+            .enumerate()
+            .map(|(index, &(white_elo, black_elo))| Game {
+                game_id: (index + 1).to_string(),
+                white_elo,
+                black_elo,
+                ..Default::default()
+            })
+            .collect();
+
+        let histogram = rating_histogram(&games, 200);
+        assert_eq!(histogram.into_iter().collect::<Vec<_>>(), vec![(1400, 3), (1600, 2), (2000, 1)]);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("rating_histogram.csv");
+        export_rating_histogram(&rating_histogram(&games, 200), file_path.to_str().unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "bucket_floor,count\n1400,3\n1600,2\n2000,1\n");
+    }
+
+    #[test]
+    fn test_elo_assortativity() {
+        let mut games: Vec<Game> = [(1500, 1510), (1800, 1810), (2100, 2110), (2400, 2410)]
+            .iter()
+            .enumerate()
+            .map(|(index, &(white_elo, black_elo))| Game {
+                game_id: (index + 1).to_string(),
+                white_elo: Some(white_elo),
+                black_elo: Some(black_elo),
+                ..Default::default()
+            })
+            .collect();
+        games.push(Game { game_id: "5".to_string(), white_elo: Some(2400), black_elo: None, ..Default::default() });
+
+        assert!((elo_assortativity(&games) - 1.0).abs() < 1e-9);
+        assert_eq!(elo_assortativity(&games[..1]), 0.0);
+    }
+
+    #[test]
+    fn test_top_player() {
+        let mut graph = DiGraph::new();
+        let player1 = graph.add_node("Player1".to_string());
+        let player2 = graph.add_node("Player2".to_string());
+        let player3 = graph.add_node("Player3".to_string());
+
+        let scores = HashMap::from([(player1, 0.2), (player2, 0.7), (player3, 0.1)]);
+        assert_eq!(top_player(&scores, &graph), Some(("Player2", 0.7)));
+
+        let tied = HashMap::from([(player3, 0.5), (player2, 0.5), (player1, 0.1)]);
+        assert_eq!(top_player(&tied, &graph), Some(("Player2", 0.5)));
+        assert_eq!(top_player(&HashMap::new(), &graph), None);
+    }
+
+    #[test]
+    fn test_graph_diff() {
+        let old_games = decisive_games(&[("Player1", "Player2"), ("Player2", "Player3")]);
+        let mut new_games = decisive_games(&[("Player3", "Player4"), ("Player1", "Player2"), ("Player2", "Player3")]);
+        new_games.extend(decisive_games(&[("Player1", "Player2")]));
+        let old = build_graph(&old_games);
+        let new = build_graph(&new_games);
+
+        let diff = graph_diff(&old, &new);
+        assert_eq!(diff, GraphDiff {
+            added_players: vec!["Player4".to_string()],
+            removed_players: vec![],
+            changed_edges: vec![
+                ("Player1".to_string(), "Player2".to_string(), 1, 2),
+                ("Player3".to_string(), "Player4".to_string(), 0, 1),
+            ],
+        });
+        assert_eq!(graph_diff(&new, &new), GraphDiff::default());
+        assert_eq!(graph_diff(&new, &old).removed_players, vec!["Player4".to_string()]);
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("graph_diff.csv");
+        export_graph_diff(&diff, file_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "added_player,Player4,,,\nchanged_edge,Player1,Player2,1,2\nchanged_edge,Player3,Player4,0,1\n"
+        );
+    }
+
+    #[test]
+    fn test_write_graph_diff() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("may.csv");
+        let new = dir.path().join("june.csv");
+        std::fs::write(&old, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\n").unwrap();
+        std::fs::write(&new, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player3,Player1,1-0\n").unwrap();
+        let output = dir.path().join("graph_diff.csv");
+
+        crate::write_graph_diff(&old, &new, &output, &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "added_player,Player3,,,\nchanged_edge,Player3,Player1,0,1\n");
+    }
+
+    #[test]
+    fn test_shortest_path_between() {
+        let games = decisive_games(&[
+            ("Player1", "Player2"),
+            ("Player2", "Player3"),
+            ("Player3", "Player4"),
+            ("Player1", "Player5"),
+            ("Player5", "Player2"),
+        ]);
+        let graph = build_graph(&games);
+
+        assert_eq!(
+            shortest_path_between(&graph, "Player1", "Player4"),
+            Some(vec!["Player1".to_string(), "Player2".to_string(), "Player3".to_string(), "Player4".to_string()])
+        );
+        assert_eq!(shortest_path_between(&graph, "Player1", "Player1"), Some(vec!["Player1".to_string()]));
+        assert_eq!(shortest_path_between(&graph, "Player4", "Player1"), None);
+        assert_eq!(shortest_path_between(&graph, "Player1", "Nobody"), None);
+    }
+
+    #[test]
+    fn test_find_intransitive_triples() {
+        let games = decisive_games(&[
+            ("Player2", "Player3"),
+            ("Player1", "Player2"),
+            ("Player3", "Player1"),
+            ("Player4", "Player1"),
+            ("Player4", "Player2"),
+            ("Player2", "Player5"),
+        ]);
+        let graph = build_graph(&games);
+
+        assert_eq!(
+            find_intransitive_triples(&graph),
+            vec![("Player2".to_string(), "Player3".to_string(), "Player1".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_calculate_rating_stats() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                white_rating_diff: Some(6.0),
+                black_rating_diff: Some(-6.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player3".to_string(),
+                black: "Player1".to_string(),
+                white_rating_diff: Some(2.0),
+                black_rating_diff: Some(-2.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player1".to_string(),
+                black: "Player3".to_string(),
+                white_rating_diff: Some(8.0),
+                black_rating_diff: Some(-8.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "4".to_string(),
+                white: "Player2".to_string(),
+                black: "Player4".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let rating_stats = calculate_rating_stats(&games, NameNormalization::Exact);
+
+        let player1 = &rating_stats["Player1"];
+        assert_eq!(player1.count, 3);
+        assert_eq!(player1.mean, 4.0);
+        assert_eq!(player1.median, 6.0);
+        assert!((player1.std_dev - (56.0f64 / 3.0).sqrt()).abs() < 1e-9);
+
+        let player2 = &rating_stats["Player2"];
+        assert_eq!(player2.count, 1);
+        assert_eq!(player2.median, -6.0);
+        assert_eq!(player2.std_dev, 0.0);
+
+        assert_eq!(rating_stats["Player3"].median, -3.0);
+        // Game 4 has no rating diffs, so it adds nothing.
+        assert!(!rating_stats.contains_key("Player4"));
+        assert_eq!(rating_stats.len(), 3);
+    }
+
+    #[test]
+    fn test_rating_volatility() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                white_rating_diff: Some(10.0),
+                black_rating_diff: Some(-10.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                white_rating_diff: Some(10.0),
+                black_rating_diff: Some(-10.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player3".to_string(),
+                black: "Player4".to_string(),
+                white_rating_diff: Some(5.0),
+                black_rating_diff: Some(-5.0),
+                ..Default::default()
+            },
+            Game {
+                game_id: "4".to_string(),
+                white: "Player1".to_string(),
+                black: "Player5".to_string(),
+                black_rating_diff: Some(-3.0),
+                ..Default::default()
+            },
+        ];
+
+        let volatility = rating_volatility(&games, NameNormalization::Exact);
+
+        // Player1 had +10 as white and -10 as black: mean 0, variance 100.
+        // Game 4 has no diff for them, and counting it as 0 would pull the
+        // spread down to sqrt(200 / 3).
+        assert_eq!(volatility["Player1"], 10.0);
+        assert_eq!(volatility["Player2"], 10.0);
+        assert_eq!(volatility["Player3"], 0.0);
+        assert_eq!(volatility["Player5"], 0.0);
+        assert_eq!(volatility.len(), 5);
+    }
+
+    #[test]
+    fn test_scan_and_stream_csv_files() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        std::fs::write(&first, "GameID,White\n1,Player1\n2,Player2\n").unwrap();
+        std::fs::write(&second, "GameID,White\n3,Player3\n").unwrap();
+
+        let files = [first.to_str().unwrap(), second.to_str().unwrap()];
+        let (header, total_rows) = scan_csv_files(&files, false).unwrap();
+        assert_eq!(header, "GameID,White");
+        assert_eq!(total_rows, 3);
+
+        let rows: Vec<String> = combined_rows(&files, &header).map(|row| row.unwrap()).collect();
+        assert_eq!(rows, vec!["1,Player1", "2,Player2", "3,Player3"]);
+    }
+
+    #[test]
+    fn test_scan_csv_files_skips_empty_files() {
+        let dir = tempdir().unwrap();
+        let empty = dir.path().join("empty.csv");
+        let header_only = dir.path().join("header_only.csv");
+        let games = dir.path().join("games.csv");
+        std::fs::write(&empty, "").unwrap();
+        std::fs::write(&header_only, "GameID,White
+").unwrap();
+        std::fs::write(&games, "GameID,White
+1,Player1
+").unwrap();
+
+        let files = [empty.to_str().unwrap(), header_only.to_str().unwrap(), games.to_str().unwrap()];
+        let (header, total_rows) = scan_csv_files(&files, false).unwrap();
+        assert_eq!(header, "GameID,White");
+        assert_eq!(total_rows, 1);
+
+        let rows: Vec<String> = combined_rows(&files, &header).map(|row| row.unwrap()).collect();
+        assert_eq!(rows, vec!["1,Player1"]);
+    }
+
+    #[test]
+    fn test_pipeline_runs_with_verbose_logging() {
+        init_logging(true);
+        assert!(log::log_enabled!(log::Level::Debug) || std::env::var("RUST_LOG").is_ok());
+
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("games.csv");
+        let subset = dir.path().join("subset_1.csv");
+        std::fs::write(&input, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player2,Player3,0-1\n").unwrap();
+
+        let files = [input.to_str().unwrap()];
+        let (header, total_rows) = scan_csv_files(&files, false).unwrap();
+        crate::data_distribution::distribute_data(combined_rows(&files, &header), total_rows, &header, &[subset.to_str().unwrap()], None, None, crate::data_distribution::SplitStrategy::Contiguous).unwrap();
+        let file_analysis = analyze_file(subset.to_str().unwrap(), &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        assert_eq!(file_analysis.games.len(), 2);
+    }
+
+    #[test]
+    fn test_read_games_from_ndjson() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("games.ndjson");
+        std::fs::write(
+            &file,
+            concat!(
+                "{\"game_id\": \"g1\", \"white\": \"Player1\", \"black\": \"Player2\", \"result\": \"1-0\", \"white_elo\": 1500, \"site\": \"lichess\"}\n",
+                "\n",
+                "{\"GameID\": \"g2\", \"White\": \"Player2\", \"Black\": \"Player1\", \"Result\": \"1/2-1/2\", \"WhiteRatingDiff\": -1.5, \"UTCDate\": \"2016.06.30\"}\n",
+            ),
+        )
+        .unwrap();
+
+        let games = read_games_from_ndjson(file.to_str().unwrap()).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!((games[0].game_id.as_str(), games[0].white.as_str(), games[0].black.as_str()), ("g1", "Player1", "Player2"));
+        assert_eq!(games[0].white_elo, Some(1500));
+        assert_eq!(games[0].black_elo, None);
+        assert_eq!((games[1].game_id.as_str(), games[1].result.as_str()), ("g2", "1/2-1/2"));
+        assert_eq!(games[1].white_rating_diff, Some(-1.5));
+        assert_eq!(games[1].date.as_deref(), Some("2016.06.30"));
+
+        std::fs::write(&file, "{\"white\": \"Player1\"}\nnot json\n").unwrap();
+        let err = read_games_from_ndjson(file.to_str().unwrap()).unwrap_err().to_string();
+        assert!(err.contains("line 2"));
+    }
+
+    #[test]
+    fn test_dry_run_writes_nothing() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("games.csv");
+        std::fs::write(&file, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\n").unwrap();
+
+        dry_run(&[file.to_str().unwrap()], false).unwrap();
+        let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(entries, vec!["games.csv"]);
+
+        std::fs::write(&file, "GameID,White,Black\ng1,Player1,Player2\n").unwrap();
+        let err = dry_run(&[file.to_str().unwrap()], false).unwrap_err().to_string();
+        assert!(err.contains("Result"));
+    }
+
+    #[test]
+    fn test_metrics_written_per_input() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        std::fs::write(&first, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\n").unwrap();
+        std::fs::write(&second, "GameID,White,Black,Result\ng2,Player3,Player4,0-1\n").unwrap();
+        let metrics_dir = dir.path().join("out");
+        let output_file = dir.path().join("analysis_output.csv");
+
+        let files = [first.to_str().unwrap(), second.to_str().unwrap()];
+        crate::perform_game_data_analysis(&files, dir.path(), &FilterOptions::default(), &GraphOptions::default(), &crate::ExportOptions::default(), &mut crate::Manifest::default()).unwrap();
+
+        let first_scores = std::fs::read_to_string(metrics_dir.join("first").join("pr_scores.csv")).unwrap();
+        let second_scores = std::fs::read_to_string(metrics_dir.join("second").join("pr_scores.csv")).unwrap();
+        assert!(first_scores.contains("Player1") && !first_scores.contains("Player3"));
+        assert!(second_scores.contains("Player3") && !second_scores.contains("Player1"));
+
+        let combined = std::fs::read_to_string(&output_file).unwrap();
+        assert!(combined.contains("Player2") && combined.contains("Player4"));
+    }
+
+    #[test]
+    fn test_analysis_output_keeps_every_row() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        std::fs::write(&first, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player2,Player3,1-0\n").unwrap();
+        std::fs::write(&second, "GameID,White,Black,Result\ng3,Player4,Player5,0-1\ng4,Player6,Player4,1-0\n").unwrap();
+
+        let files = [first.to_str().unwrap(), second.to_str().unwrap()];
+        crate::perform_game_data_analysis(&files, dir.path(), &FilterOptions::default(), &GraphOptions::default(), &crate::ExportOptions::default(), &mut crate::Manifest::default()).unwrap();
+
+        // The per-metric files have no header, so the top-ranked row of each
+        // must still make it into the combined file.
+        let mut combined = csv::Reader::from_path(dir.path().join("analysis_output.csv")).unwrap();
+        let combined_pagerank: Vec<(String, String)> = combined
+            .records()
+            .map(|record| record.unwrap())
+            .filter(|record| &record[0] == "PageRank")
+            .map(|record| (record[1].to_string(), record[2].to_string()))
+            .collect();
+        let mut exported_pagerank = Vec::new();
+        for name in ["first", "second"] {
+            let mut reader = csv::ReaderBuilder::new().has_headers(false).from_path(dir.path().join("out").join(name).join("pr_scores.csv")).unwrap();
+            exported_pagerank.extend(reader.records().map(|record| {
+                let record = record.unwrap();
+                (record[0].to_string(), record[1].to_string())
+            }));
+        }
+        assert_eq!(exported_pagerank.len(), 6);
+        assert_eq!(combined_pagerank, exported_pagerank);
+    }
+
+    #[test]
+    fn test_wide_analysis_output() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("games.csv");
+        std::fs::write(&input, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player2,Player3,1/2-1/2\n").unwrap();
+
+        let export_options = crate::ExportOptions { wide: true, ..Default::default() };
+        crate::perform_game_data_analysis(&[input.to_str().unwrap()], dir.path(), &FilterOptions::default(), &GraphOptions::default(), &export_options, &mut crate::Manifest::default()).unwrap();
+
+        let combined = std::fs::read_to_string(dir.path().join("analysis_output.csv")).unwrap();
+        let lines: Vec<&str> = combined.lines().collect();
+        assert_eq!(lines[0], "player,pagerank,betweenness,closeness,win_rate,games,mean_rating_diff,distinct_opponents,upsets,elo_delta,longest_win_streak,strength_in,strength_out");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("Player1,"));
+        assert!(!combined.contains("Summary"));
+    }
+
+    #[test]
+    fn test_manifest_lists_exported_files() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("games.csv");
+        std::fs::write(&input, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player2,Player3,1/2-1/2\n").unwrap();
+
+        let mut manifest = crate::Manifest::default();
+        crate::perform_game_data_analysis(&[input.to_str().unwrap()], dir.path(), &FilterOptions::default(), &GraphOptions::default(), &crate::ExportOptions::default(), &mut manifest).unwrap();
+        let manifest_path = dir.path().join("manifest.json");
+        manifest.write(&manifest_path).unwrap();
+
+        #[derive(serde::Deserialize)]
+        struct Written {
+            files: Vec<Entry>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Entry {
+            path: String,
+            rows: Option<usize>,
+            description: String,
+        }
+        let written: Written = serde_json::from_reader(std::fs::File::open(&manifest_path).unwrap()).unwrap();
+        let entry = |name: &str| {
+            written
+                .files
+                .iter()
+                .find(|entry| std::path::Path::new(&entry.path).file_name().is_some_and(|file_name| file_name == name))
+                .unwrap_or_else(|| panic!("{} missing from the manifest", name))
+        };
+        assert_eq!(entry("pr_scores.csv").rows, Some(3));
+        assert_eq!(entry("weighted_pr_scores.csv").rows, Some(3));
+        assert_eq!(entry("player_perf.csv").rows, Some(3));
+        assert_eq!(entry("roster.csv").rows, Some(3));
+        assert_eq!(entry("adjacency_matrix.csv").rows, Some(3));
+        assert_eq!(entry("player_graph.dot").rows, None);
+        assert!(!entry("analysis_output.csv").description.is_empty());
+        assert!(written.files.iter().all(|entry| std::path::Path::new(&entry.path).exists()));
+    }
+
+    #[test]
+    fn test_seeded_runs_are_identical() {
+        let games = "GameID,White,Black,Result,WhiteElo,BlackElo,WhiteRatingDiff,BlackRatingDiff,ECO,Opening,TimeControl,Moves\n\
+            g1,Player1,Player2,1-0,1500,1480,8.0,-8.0,C20,King's Pawn,300+0,e4 e5 Qh5 Nc6 Bc4 Nf6 Qxf7#\n\
+            g2,Player2,Player3,1/2-1/2,1480,1510,0.0,0.0,B01,Scandinavian,60+0,e4 d5\n\
+            g3,Player3,Player4,0-1,1510,1620,-9.0,9.0,C20,King's Pawn,600+5,e4 e5\n\
+            g4,Player4,Player1,1-0,1620,1500,5.0,-5.0,A00,Polish,300+0,b4\n\
+            g5,Player5,Player2,0-1,1400,1480,-6.0,6.0,B01,Scandinavian,180+2,e4 d5\n";
+        let graph_options = GraphOptions { betweenness_samples: Some(2), seed: Some(7), ..Default::default() };
+        let run = || {
+            let dir = tempdir().unwrap();
+            let input = dir.path().join("games.csv");
+            std::fs::write(&input, games).unwrap();
+            crate::perform_game_data_analysis(&[input.to_str().unwrap()], dir.path(), &FilterOptions::default(), &graph_options, &crate::ExportOptions::default(), &mut crate::Manifest::default()).unwrap();
+            let mut outputs = vec![("analysis_output.csv".to_string(), std::fs::read(dir.path().join("analysis_output.csv")).unwrap())];
+            for entry in std::fs::read_dir(dir.path().join("out").join("games")).unwrap() {
+                let path = entry.unwrap().path();
+                outputs.push((path.file_name().unwrap().to_string_lossy().into_owned(), std::fs::read(&path).unwrap()));
+            }
+            outputs.sort();
+            outputs
+        };
+
+        let first = run();
+        let second = run();
+        assert_eq!(first.len(), second.len());
+        for ((name, a), (_, b)) in first.iter().zip(&second) {
+            assert!(a == b, "{} differs between runs", name);
+        }
+    }
+
+    #[test]
+    fn test_metrics_dirs_disambiguates_stems() {
+        use std::path::Path;
+        let dirs = crate::metrics_dirs(Path::new("out"), &["a/games.csv", "b/games.csv", "other.csv.gz"]);
+        assert_eq!(dirs, vec![Path::new("out/games"), Path::new("out/games_2"), Path::new("out/other.csv")]);
+    }
+
+    #[test]
+    fn test_analyze_header_only_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("games.csv");
+        std::fs::write(&file, "GameID,White,Black,Result,WhiteElo,BlackElo\n").unwrap();
+
+        let file_analysis = analyze_file(file.to_str().unwrap(), &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        assert!(file_analysis.games.is_empty());
+        assert_eq!(file_analysis.graph.node_count(), 0);
+        assert!(file_analysis.pagerank_scores.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_pgn_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("games.pgn");
+        std::fs::write(
+            &file,
+            "[White \"Player1\"]\n[Black \"Player2\"]\n[Result \"1-0\"]\n\n1. e4 e5 1-0\n\n\
+             [White \"Player2\"]\n[Black \"Player3\"]\n[Result \"0-1\"]\n\n1. d4 d5 0-1\n",
+        )
+        .unwrap();
+        assert_eq!(crate::input::input_format(&file), crate::InputFormat::Pgn);
+        assert_eq!(crate::input::input_format(&dir.path().join("games.pgn.gz")), crate::InputFormat::Pgn);
+        assert_eq!(crate::input::input_format(&dir.path().join("games.csv.gz")), crate::InputFormat::Csv);
+
+        let file_analysis = analyze_file(file.to_str().unwrap(), &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        assert_eq!(file_analysis.games.len(), 2);
+        assert_eq!(file_analysis.graph.node_count(), 3);
+        assert_eq!(file_analysis.performance["Player3"].games_won, 1);
+    }
+
+    #[test]
+    fn test_analyze_ndjson_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("games.jsonl");
+        std::fs::write(
+            &file,
+            "{\"White\": \"Player1\", \"Black\": \"Player2\", \"Result\": \"1-0\"}\n\
+             {\"White\": \"Player2\", \"Black\": \"Player3\", \"Result\": \"1/2-1/2\"}\n",
+        )
+        .unwrap();
+        assert_eq!(crate::input::input_format(&file), crate::InputFormat::Ndjson);
+        assert_eq!(crate::input::input_format(&dir.path().join("games.ndjson.gz")), crate::InputFormat::Ndjson);
+
+        let file_analysis = analyze_file(file.to_str().unwrap(), &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        assert_eq!(file_analysis.games.len(), 2);
+        assert_eq!(file_analysis.graph.node_count(), 3);
+        assert_eq!(file_analysis.performance["Player2"].games_drawn, 1);
+    }
+
+    #[test]
+    fn test_analyze_parquet_file() {
+        let dir = tempdir().unwrap();
+        let csv_file = dir.path().join("subset_data_1.csv");
+        std::fs::write(&csv_file, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player2,Player3,0-1\n").unwrap();
+        let file = crate::data_distribution::write_subset_parquet(&csv_file).unwrap();
+        assert_eq!(crate::input::input_format(&file), crate::InputFormat::Parquet);
+
+        let file_analysis = analyze_file(file.to_str().unwrap(), &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        assert_eq!(file_analysis.games.len(), 2);
+        assert_eq!(file_analysis.graph.node_count(), 3);
+        assert_eq!(file_analysis.performance["Player3"].games_won, 1);
+    }
+
+    #[test]
+    fn test_scan_and_stream_gzipped_csv_files() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let contents = "GameID,White\n1,Player1\n2,Player2\n";
+        let dir = tempdir().unwrap();
+        let plain = dir.path().join("games.csv");
+        let gzipped = dir.path().join("games.csv.gz");
+        std::fs::write(&plain, contents).unwrap();
+        let mut encoder = GzEncoder::new(File::create(&gzipped).unwrap(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let plain_files = [plain.to_str().unwrap()];
+        let gzipped_files = [gzipped.to_str().unwrap()];
+        let (plain_header, plain_rows) = scan_csv_files(&plain_files, false).unwrap();
+        assert_eq!(scan_csv_files(&gzipped_files, false).unwrap(), (plain_header.clone(), plain_rows));
+
+        let plain_combined: Vec<String> = combined_rows(&plain_files, &plain_header).map(|row| row.unwrap()).collect();
+        let gzipped_combined: Vec<String> = combined_rows(&gzipped_files, &plain_header).map(|row| row.unwrap()).collect();
+        assert_eq!(gzipped_combined, plain_combined);
+    }
+
+    #[test]
+    fn test_scan_csv_files_with_reordered_columns() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        std::fs::write(&first, "GameID,White,Black\n1,Player1,Player2\n").unwrap();
+        std::fs::write(&second, "Black,GameID,White\nPlayer4,2,Player3\n").unwrap();
+
+        let files = [first.to_str().unwrap(), second.to_str().unwrap()];
+        let err = scan_csv_files(&files, false).unwrap_err().to_string();
+        assert!(err.contains("different order"));
+        assert!(err.contains("GameID, White, Black"));
+
+        let (header, total_rows) = scan_csv_files(&files, true).unwrap();
+        assert_eq!(header, "GameID,White,Black");
+        assert_eq!(total_rows, 2);
+
+        let rows: Vec<String> = combined_rows(&files, &header).map(|row| row.unwrap()).collect();
+        assert_eq!(rows, vec!["1,Player1,Player2", "2,Player3,Player4"]);
+    }
+
+    #[test]
+    fn test_analyze_files_matches_serial() {
+        let dir = tempdir().unwrap();
+        let header = "GameID,White,WhiteRatingDiff,Black,BlackRatingDiff,ECO,Result";
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        std::fs::write(&first, format!("{}\ng1,Player1,5.5,Player2,-5.5,C60,Normal\ng2,Player2,4.5,Player3,-4.5,C60,Normal\ng3,Player3,-6.5,Player1,6.5,D02,Normal\n", header)).unwrap();
+        std::fs::write(&second, format!("{}\ng4,Player4,5.5,Player5,-5.5,B01,Normal\ng5,Player5,0.0,Player6,0.0,B01,Normal\n", header)).unwrap();
+
+        let files = [first.to_str().unwrap(), second.to_str().unwrap()];
+        let filters = FilterOptions::default();
+        let graph_options = GraphOptions::default();
+        let parallel = analyze_files(&files, &filters, &graph_options).unwrap();
+        let serial: Vec<_> = files.iter().map(|file| analyze_file(file, &filters, &graph_options).unwrap()).collect();
+
+        let named = |scores: &HashMap<NodeIndex, f64>, graph: &DiGraph<String, u32>| {
+            let mut named: Vec<(String, f64)> = scores.iter().map(|(node, &score)| (graph[*node].clone(), score)).collect();
+            named.sort_by(|a, b| a.0.cmp(&b.0));
+            named
+        };
+
+        assert_eq!(parallel.len(), serial.len());
+        for (p, s) in parallel.iter().zip(serial.iter()) {
+            assert_eq!(p.games.len(), s.games.len());
+            assert_eq!(p.graph.node_count(), s.graph.node_count());
+            assert_eq!(p.graph.edge_count(), s.graph.edge_count());
+            assert_eq!(p.player_eco_classifications, s.player_eco_classifications);
+            assert_eq!(p.components, s.components);
+            for (scores_p, scores_s) in [
+                (&p.pagerank_scores, &s.pagerank_scores),
+                (&p.betweenness_centrality, &s.betweenness_centrality),
+                (&p.closeness_centrality, &s.closeness_centrality),
+                (&p.harmonic_centrality, &s.harmonic_centrality),
+            ] {
+                for ((name_p, score_p), (name_s, score_s)) in named(scores_p, &p.graph).iter().zip(named(scores_s, &s.graph).iter()) {
+                    assert_eq!(name_p, name_s);
+                    assert!((score_p - score_s).abs() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_degree_centrality_by_graph_mode() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player3".to_string(),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let directed = build_graph_with(&games, &GraphOptions { mode: GraphMode::Directed, ..Default::default() });
+        let undirected = build_graph_with(&games, &GraphOptions { mode: GraphMode::Undirected, ..Default::default() });
+        assert_eq!(directed.edge_count(), 2);
+        assert_eq!(undirected.edge_count(), 4);
+
+        let directed_degree = calculate_in_out_degree_centrality(&directed);
+        let undirected_degree = calculate_in_out_degree_centrality(&undirected);
+        let player1 = directed.node_indices().next().unwrap();
+        let player2 = directed.node_indices().nth(1).unwrap();
+
+        assert_eq!(directed_degree[&player1], (0, 1));
+        assert_eq!(undirected_degree[&player1], (1, 1));
+        assert_eq!(directed_degree[&player2], (2, 0));
+        assert_eq!(undirected_degree[&player2], (2, 2));
+    }
+
+    #[test]
+    fn test_build_graph_accumulates_edge_weights() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        // Each player won once, so there is one edge each way.
+        let graph = build_graph(&games);
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(graph.edge_weights().copied().collect::<Vec<_>>(), vec![1, 1]);
+
+        let mut rematch = games;
+        rematch.push(Game {
+            game_id: "3".to_string(),
+            white: "Player1".to_string(),
+            black: "Player2".to_string(),
+            result: "1-0".to_string(),
+            ..Default::default()
+        });
+        let graph = build_graph(&rematch);
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(graph[graph.find_edge(NodeIndex::new(0), NodeIndex::new(1)).unwrap()], 2);
+        assert_eq!(graph[graph.find_edge(NodeIndex::new(1), NodeIndex::new(0)).unwrap()], 1);
+    }
+
+    #[test]
+    fn test_weighted_centrality_uses_game_counts() {
+        let mut games = Vec::new();
+        for id in 0..3 {
+            games.push(Game {
+                game_id: format!("a{}", id),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            });
+        }
+        games.push(Game {
+            game_id: "b".to_string(),
+            white: "Player3".to_string(),
+            black: "Player2".to_string(),
+            result: "1-0".to_string(),
+            ..Default::default()
+        });
+
+        let graph = build_graph(&games);
+        let node = |name: &str| graph.node_indices().find(|&i| graph[i] == name).unwrap();
+
+        let heavy = weighted_distances(&graph, node("Player1"));
+        let light = weighted_distances(&graph, node("Player3"));
+        assert!((heavy[&node("Player2")] - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(light[&node("Player2")], 1.0);
+
+        let (weighted_betweenness, weighted_closeness) = calculate_weighted_centrality(&graph);
+        let closeness = calculate_closeness_centrality(&graph);
+        assert!((weighted_closeness[&node("Player2")] - 1.5).abs() < 1e-9);
+        assert!((closeness[&node("Player2")] - 1.0).abs() < 1e-9);
+        assert_eq!(weighted_betweenness.len(), 3);
+    }
+
+    #[test]
+    fn test_weighted_betweenness_counts_equal_length_paths() {
+        // S-A-B-T is 1/5 + 1/5 + 1/5 and S-C-T is 1/2 + 1/10, the same length
+        // but not the same float.
+        let mut games = Vec::new();
+        for (winner, loser, count) in [("S", "A", 5), ("A", "B", 5), ("B", "T", 5), ("S", "C", 2), ("C", "T", 10)] {
+            for _ in 0..count {
+                games.push(Game {
+                    white: winner.to_string(),
+                    black: loser.to_string(),
+                    result: "1-0".to_string(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        let graph = build_graph(&games);
+        let node = |name: &str| graph.node_indices().find(|&i| graph[i] == name).unwrap();
+        let (weighted_betweenness, _) = calculate_weighted_centrality(&graph);
+        // Endpoints count, so A is on 3 pairs as an endpoint, all of S->B and
+        // half of S->T; C is an endpoint of 2 pairs and on half of S->T.
+        assert!((weighted_betweenness[&node("A")] - 4.5 / 20.0).abs() < 1e-9);
+        assert!((weighted_betweenness[&node("B")] - 4.5 / 20.0).abs() < 1e-9);
+        assert!((weighted_betweenness[&node("C")] - 2.5 / 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_katz_centrality() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player3".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let graph = build_graph(&games);
+        let node = |name: &str| graph.node_indices().find(|&i| graph[i] == name).unwrap();
+
+        let katz = calculate_katz_centrality(&graph, 0.1, 1.0).unwrap();
+        assert_eq!(katz.len(), 3);
+        assert!(katz[&node("Player2")] > katz[&node("Player1")]);
+        assert!((katz[&node("Player1")] - katz[&node("Player3")]).abs() < 1e-9);
+        let norm: f64 = katz.values().map(|score| score * score).sum::<f64>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_katz_centrality_fails_to_converge_with_large_alpha() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let graph = build_graph(&games);
+        assert!(matches!(calculate_katz_centrality(&graph, 2.0, 1.0), Err(AnalysisError::Convergence(_))));
+    }
+
+    #[test]
+    fn test_build_graph_from_dataframe_matches_games_path() {
+        let df = df!(
+            "White" => &["Player1", "Player1", "Player2", "Player3"],
+            "Black" => &["Player2", "Player2", "Player3", "Player1"],
+            "Result" => &["1-0", "0-1", "1/2-1/2", "1-0"]
+        )
+        .unwrap();
+
+        let from_games = build_graph(&read_games_from_dataframe(&df).unwrap());
+        let from_dataframe = build_graph_from_dataframe(&df, &GraphOptions::default()).unwrap();
+
+        assert_eq!(from_dataframe.node_count(), from_games.node_count());
+        assert_eq!(from_dataframe.edge_count(), from_games.edge_count());
+        let find = |name: &str| from_dataframe.node_indices().find(|&i| from_dataframe[i] == name).unwrap();
+        for edge in from_games.edge_indices() {
+            let (source, target) = from_games.edge_endpoints(edge).unwrap();
+            let matching = from_dataframe.find_edge(find(&from_games[source]), find(&from_games[target])).unwrap();
+            assert_eq!(from_dataframe[matching], from_games[edge]);
+        }
+
+        // White's score as a number: Player2 beat Player1, then they drew.
+        let numeric = df!(
+            "White" => &["Player1", "Player1"],
+            "Black" => &["Player2", "Player2"],
+            "Result" => &[0.0, 0.5]
+        )
+        .unwrap();
+        let from_numeric = build_graph_from_dataframe(&numeric, &GraphOptions::default()).unwrap();
+        let (player1, player2) = (NodeIndex::new(0), NodeIndex::new(1));
+        assert_eq!(from_numeric[from_numeric.find_edge(player2, player1).unwrap()], 2);
+        assert_eq!(from_numeric[from_numeric.find_edge(player1, player2).unwrap()], 1);
+
+        let missing_result = df!("White" => &["Player1"], "Black" => &["Player2"]).unwrap();
+        assert!(matches!(build_graph_from_dataframe(&missing_result, &GraphOptions::default()), Err(AnalysisError::MissingColumn(name)) if name == "Result"));
+    }
+
+    // Not run by default: `cargo test --release -- --ignored --nocapture bench_graph_from_dataframe`
+    #[test]
+    #[ignore]
+    fn bench_graph_from_dataframe() {
+        const ROWS: usize = 400_000;
+        const PLAYERS: usize = 5_000;
+        let white: Vec<String> = (0..ROWS).map(|i| format!("Player{}", i % PLAYERS)).collect();
+        let black: Vec<String> = (0..ROWS).map(|i| format!("Player{}", (i * 7 + 1) % PLAYERS)).collect();
+        let result: Vec<&str> = (0..ROWS).map(|i| ["1-0", "0-1", "1/2-1/2"][i % 3]).collect();
+        let eco: Vec<&str> = (0..ROWS).map(|i| ["C20", "B01", "A00"][i % 3]).collect();
+        let opening: Vec<&str> = (0..ROWS).map(|i| ["King's Pawn Game", "Scandinavian Defense", "Polish Opening"][i % 3]).collect();
+        let moves: Vec<&str> = (0..ROWS).map(|_| "e4 e5 Nf3 Nc6 Bb5 a6 Ba4 Nf6 O-O Be7").collect();
+        let white: Vec<&str> = white.iter().map(String::as_str).collect();
+        let black: Vec<&str> = black.iter().map(String::as_str).collect();
+        let df = df!(
+            "White" => white.as_slice(),
+            "Black" => black.as_slice(),
+            "Result" => result.as_slice(),
+            "ECO" => eco.as_slice(),
+            "Opening" => opening.as_slice(),
+            "Moves" => moves.as_slice()
+        )
+        .unwrap();
+        let options = GraphOptions::default();
+
+        let start = std::time::Instant::now();
+        let games = read_games_from_dataframe(&df).unwrap();
+        let from_games = build_graph_with(&games, &options);
+        let games_time = start.elapsed();
+        let game_bytes: usize = games.capacity() * std::mem::size_of::<Game>()
+            + games
+                .iter()
+                .map(|game| {
+                    [&game.game_id, &game.event, &game.white, &game.black, &game.moves, &game.eco, &game.opening, &game.time_control, &game.result]
+                        .iter()
+                        .map(|field| field.capacity())
+                        .sum::<usize>()
+                })
+                .sum::<usize>();
+
+        let start = std::time::Instant::now();
+        let from_dataframe = build_graph_from_dataframe(&df, &options).unwrap();
+        let dataframe_time = start.elapsed();
+
+        assert_eq!(from_games.edge_count(), from_dataframe.edge_count());
+        println!("{} rows, {} players", ROWS, PLAYERS);
+        println!("read_games_from_dataframe + build_graph_with: {:?}, Vec<Game> held {:.1} MB", games_time, game_bytes as f64 / 1e6);
+        println!("build_graph_from_dataframe: {:?}", dataframe_time);
+    }
+
+    #[test]
+    fn test_percentile_ranks() {
+        let ranks = percentile_ranks([("Player1", 0.9), ("Player2", 0.5), ("Player3", 0.5), ("Player4", 0.1)]);
+        assert_eq!(ranks["Player1"], 100.0);
+        assert_eq!(ranks["Player2"], 62.5);
+        assert_eq!(ranks["Player3"], 62.5);
+        assert_eq!(ranks["Player4"], 25.0);
+        assert!(percentile_ranks(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_export_overwrites_longer_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("player_perf.csv");
+        let mut performance = HashMap::new();
+        for player in ["Player1", "Player2", "Player3"] {
+            let mut stats = PlayerPerformance::default();
+            stats.update("1-0", 12.5);
+            performance.insert(player.to_string(), stats);
+        }
+        export_performance(&performance, file_path.to_str().unwrap(), None).unwrap();
+
+        performance.retain(|player, _| player == "Player2");
+        export_performance(&performance, file_path.to_str().unwrap(), None).unwrap();
+
+        let contents = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+        assert!(contents.starts_with("1,Player2,1,1,0,0,12.5,1.0,"));
+        assert!(contents.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_export_performance_sorted_by_win_rate() {
+        let mut performance = HashMap::new();
+        for (player, won, lost) in [("Player1", 0, 2), ("Player2", 2, 0), ("Player3", 1, 1), ("Player4", 1, 0)] {
+            let mut stats = PlayerPerformance::default();
+            for _ in 0..won {
+                stats.update("1-0", 0.0);
+            }
+            for _ in 0..lost {
+                stats.update("0-1", 0.0);
+            }
+            performance.insert(player.to_string(), stats);
+        }
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("player_perf.csv");
+        export_performance(&performance, file_path.to_str().unwrap(), None).unwrap();
+
+        let mut contents = String::new();
+        File::open(&file_path).unwrap().read_to_string(&mut contents).unwrap();
+        let rows: Vec<&str> = contents.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[0].starts_with("1,Player2,2,2,0,0,"));
+        assert!(rows[1].starts_with("2,Player4,1,1,0,0,"));
+        assert!(rows[2].starts_with("3,Player3,2,1,1,0,"));
+        assert!(rows[3].starts_with("4,Player1,2,0,2,0,"));
+        // Player2 and Player4 share the top two ranks, and everyone's rating
+        // change is 0 so all four share the average rank there.
+        assert!(rows[0].ends_with(",1.0,87.5,62.5"));
+        assert!(rows[1].ends_with(",1.0,87.5,62.5"));
+        assert!(rows[2].ends_with(",0.5,50.0,62.5"));
+        assert!(rows[3].ends_with(",0.0,25.0,62.5"));
+
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player3".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+        ];
+        let graph = build_graph(&games);
+        let mut scores = HashMap::new();
+        for node in graph.node_indices() {
+            let score = if graph[node] == "Player1" { 0.5 } else { 0.25 };
+            scores.insert(node, score);
+        }
+        let scores_path = dir.path().join("scores.csv");
+        export_centrality_data(&scores, &graph, scores_path.to_str().unwrap(), None).unwrap();
+        let mut contents = String::new();
+        File::open(&scores_path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), vec!["Player1,0.5", "Player2,0.25", "Player3,0.25"]);
+    }
+
+    #[test]
+    fn test_export_centrality_data_top_n() {
+        let games: Vec<Game> = (1..5)
+            .map(|i| Game {
+                game_id: format!("g{}", i),
+                white: format!("Player{}", i),
+                black: format!("Player{}", i + 1),
+                result: "1-0".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let graph = build_graph(&games);
+        assert_eq!(graph.node_count(), 5);
+        let scores: HashMap<NodeIndex, f64> = graph.node_indices().map(|node| (node, node.index() as f64)).collect();
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("scores.csv");
+        export_centrality_data(&scores, &graph, file_path.to_str().unwrap(), Some(2)).unwrap();
+
+        let mut contents = String::new();
+        File::open(&file_path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), vec!["Player5,4.0", "Player4,3.0"]);
+    }
+
+    #[test]
+    fn test_normalize_names_collapses_variants() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "MagnusCarlsen".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "magnuscarlsen".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player2".to_string(),
+                black: " MagnusCarlsen ".to_string(),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(normalize_name(" MagnusCarlsen "), "magnuscarlsen");
+        assert_eq!(build_graph(&games).node_count(), 4);
+        let trimmed = build_graph_with(&games, &GraphOptions { names: NameNormalization::Trim, ..Default::default() });
+        assert_eq!(trimmed.node_count(), 3);
+
+        let options = GraphOptions { names: NameNormalization::TrimAndCaseFold, ..Default::default() };
+        let graph = build_graph_with(&games, &options);
+        assert_eq!(graph.node_count(), 2);
+        assert!(graph.node_indices().any(|node| graph[node] == "MagnusCarlsen"));
+
+        let performance = track_player_performance_with(&games, NameNormalization::TrimAndCaseFold);
+        assert!(performance.contains_key("MagnusCarlsen"));
+        assert!(!performance.contains_key("magnuscarlsen"));
+
+        // The other per-player aggregations use the graph's names too.
+        let metrics = calculate_mean_mode(&games, NameNormalization::TrimAndCaseFold);
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics["MagnusCarlsen"].3, 3);
+        let color_split = track_color_split_performance(&games, NameNormalization::TrimAndCaseFold);
+        assert_eq!(color_split["MagnusCarlsen"].white.games_played, 2);
+        assert_eq!(color_split["MagnusCarlsen"].black.games_played, 1);
+        let eco_counts = crate::strategy_analysis::player_eco_counts(&games, NameNormalization::TrimAndCaseFold);
+        assert_eq!(eco_counts["MagnusCarlsen"][""], 3);
+        assert!(graph.node_weights().all(|player| metrics.contains_key(player) && eco_counts.contains_key(player)));
+    }
+
+    #[test]
+    fn test_normalize_names_follows_skipped_games() {
+        // The first spelling of Carol is in a game with no readable result, so
+        // aggregations that skip it must still use the graph's name.
+        let games = vec![
+            Game { white: " carol ".to_string(), black: "Bob".to_string(), result: "*".to_string(), ..Default::default() },
+            Game { white: "Carol".to_string(), black: "Bob".to_string(), result: "1-0".to_string(), ..Default::default() },
+        ];
+        let normalization = NameNormalization::TrimAndCaseFold;
+
+        let graph = build_graph_with(&games, &GraphOptions { names: normalization, ..Default::default() });
+        assert!(graph.node_weights().any(|player| player == "carol"));
+
+        let performance = track_player_performance_with(&games, normalization);
+        assert_eq!(performance["carol"].games_won, 1);
+        assert!(!performance.contains_key("Carol"));
+        let color_split = track_color_split_performance(&games, normalization);
+        assert_eq!(color_split["carol"].white.games_played, 1);
+    }
+
+    #[test]
+    fn test_player_table_maps_use_normalized_names() {
+        let game = |white: &str, white_elo, black: &str, black_elo, result: &str| Game {
+            white: white.to_string(),
+            white_elo: Some(white_elo),
+            black: black.to_string(),
+            black_elo: Some(black_elo),
+            result: result.to_string(),
+            ..Default::default()
+        };
+        let games = vec![
+            game("Carol", 1500, "Bob", 1800, "1-0"),
+            game("Bob", 1800, " carol ", 1500, "0-1"),
+        ];
+        let normalization = NameNormalization::TrimAndCaseFold;
+        let graph = build_graph_with(&games, &GraphOptions { names: normalization, ..Default::default() });
+
+        let upsets = upset_counts(&games, normalization);
+        let deltas = elo_performance_delta(&games, normalization);
+        let streaks = longest_win_streak(&games, normalization);
+        assert_eq!(upsets["Carol"], 2);
+        assert_eq!(streaks["Carol"], 2);
+        assert!(graph.node_weights().all(|player| deltas.contains_key(player) && streaks.contains_key(player)));
+        assert_eq!(deltas.len(), 2);
+    }
+
+    #[test]
+    fn test_analysis_summary() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("games.csv");
+        std::fs::write(&file, "GameID,White,Black,Result\ng1,Player1,Player2,1-0\ng2,Player2,Player3,1/2-1/2\ng3,Player3,Player1,0-1\ng4,Player1,Player3,1/2-1/2\n").unwrap();
+
+        let file_analysis = analyze_file(file.to_str().unwrap(), &FilterOptions::default(), &GraphOptions::default()).unwrap();
+        let mut summary = AnalysisSummary::default();
+        let upsets = HashMap::from([("Player1".to_string(), 2), ("Player3".to_string(), 1)]);
+        let elo_deltas = HashMap::from([("Player1".to_string(), 0.4), ("Player2".to_string(), -0.3), ("Player3".to_string(), 0.4)]);
+        summary.add(&file_analysis.games, &file_analysis.graph, &file_analysis.pagerank_scores, &upsets, &elo_deltas);
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        summary.write(&mut writer).unwrap();
+        let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        assert!(written.contains("Summary,Total Games,4,"));
+        assert!(written.contains("Summary,Distinct Players,3,"));
+        assert!(written.contains("Summary,Draw Percentage,50.00,"));
+        assert!(written.contains("Summary,Top PageRank Player,"));
+        assert!(written.contains("Summary,Upsets,3,"));
+        assert!(written.contains("Summary,Top Elo Delta Player,Player1,0.40,"));
+        assert!(written.contains("Summary,Graph Density,0.8333,"));
+    }
+
+    fn draw_policy_games() -> Vec<Game> {
+        vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "0-1".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player3".to_string(),
+                result: "1/2-1/2".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player3".to_string(),
+                black: "Player4".to_string(),
+                result: "*".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn edge_names(graph: &DiGraph<String, u32>) -> Vec<(String, String)> {
+        let mut edges: Vec<(String, String)> = graph
+            .edge_indices()
+            .map(|edge| {
+                let (source, target) = graph.edge_endpoints(edge).unwrap();
+                (graph[source].clone(), graph[target].clone())
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    fn edge(source: &str, target: &str) -> (String, String) {
+        (source.to_string(), target.to_string())
+    }
+
+    #[test]
+    fn test_build_graph_bidirectional_draws() {
+        let graph = build_graph_with(&draw_policy_games(), &GraphOptions { draws: DrawPolicy::Bidirectional, ..Default::default() });
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(edge_names(&graph), vec![
+            edge("Player2", "Player1"),
+            edge("Player2", "Player3"),
+            edge("Player3", "Player2"),
+        ]);
+        assert_eq!(build_graph(&draw_policy_games()).edge_count(), 3);
+    }
+
+    #[test]
+    fn test_build_graph_skipped_draws() {
+        let games = draw_policy_games();
+        let graph = build_graph_with(&games, &GraphOptions { draws: DrawPolicy::Skip, ..Default::default() });
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(edge_names(&graph), vec![edge("Player2", "Player1")]);
+
+        let undirected = build_graph_with(&games, &GraphOptions { mode: GraphMode::Undirected, draws: DrawPolicy::Skip, ..Default::default() });
+        assert_eq!(edge_names(&undirected), vec![edge("Player1", "Player2"), edge("Player2", "Player1")]);
+    }
+
+    #[test]
+    fn test_sampled_betweenness_centrality() {
+        let games = decisive_games(&[
+            ("Player1", "Player2"),
+            ("Player2", "Player3"),
+            ("Player3", "Player4"),
+            ("Player4", "Player5"),
+            ("Player2", "Player5"),
+            ("Player5", "Player1"),
+        ]);
+        let graph = build_graph(&games);
+
+        let sampled = calculate_betweenness_centrality(&graph, Some(2), 7);
+        assert_eq!(sampled.len(), graph.node_count());
+        assert!(sampled.values().all(|score| score.is_finite() && *score >= 0.0));
+        assert!(sampled.values().any(|score| *score > 0.0));
+        assert_eq!(calculate_betweenness_centrality(&graph, Some(2), 7), sampled);
+
+        // Asking for at least every player is the exact calculation.
+        let exact = calculate_betweenness_centrality(&graph, None, 0);
+        assert_eq!(calculate_betweenness_centrality(&graph, Some(graph.node_count()), 7), exact);
+    }
+
+    #[test]
+    fn test_build_graph_edge_modes() {
+        let games = decisive_games(&[("Player1", "Player2"), ("Player1", "Player2"), ("Player2", "Player3"), ("Player1", "Player2")]);
+
+        let collapsed = build_graph_with(&games, &GraphOptions { edges: EdgeMode::Collapsed, ..Default::default() });
+        assert_eq!(collapsed.edge_count(), 2);
+        assert_eq!(build_graph(&games).edge_count(), 2);
+        let weights: Vec<u32> = collapsed.edge_weights().copied().collect();
+        assert_eq!(weights, vec![3, 1]);
+
+        let multi = build_graph_with(&games, &GraphOptions { edges: EdgeMode::Multi, ..Default::default() });
+        assert_eq!(multi.node_count(), 3);
+        assert_eq!(multi.edge_count(), 4);
+        assert!(multi.edge_weights().all(|&weight| weight == 1));
+        assert_eq!(crate::analysis::graph_diff(&collapsed, &multi), crate::analysis::GraphDiff::default());
+        assert_eq!(graph_reciprocity(&multi), graph_reciprocity(&collapsed));
+    }
+
+    #[test]
+    fn test_normalize_scores() {
+        let graph = build_graph(&draw_policy_games());
+        let nodes: Vec<NodeIndex> = graph.node_indices().collect();
+        let scores: HashMap<NodeIndex, f64> = [(nodes[0], 0.1), (nodes[1], 0.4), (nodes[2], 0.25), (nodes[3], 0.1)].into_iter().collect();
+
+        let normalized = normalize_scores(&scores);
+        assert_eq!(normalized[&nodes[0]], 0.0);
+        assert_eq!(normalized[&nodes[1]], 100.0);
+        assert!((normalized[&nodes[2]] - 50.0).abs() < 1e-9);
+
+        let equal: HashMap<NodeIndex, f64> = nodes.iter().map(|&node| (node, 0.25)).collect();
+        assert!(normalize_scores(&equal).values().all(|&score| score == 50.0));
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("pr_scores.csv");
+        export_centrality_data_with_normalized(&scores, &graph, file_path.to_str().unwrap(), Some(1)).unwrap();
+        let mut contents = String::new();
+        File::open(&file_path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), vec!["Player2,0.4,100.0"]);
+    }
 }
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use csv::Writer;
+use crate::analysis::Game;
+use crate::error::AnalysisError;
+
+fn is_result_token(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+pub fn parse_moves(moves: &str) -> Vec<String> {
+    let mut without_comments = String::with_capacity(moves.len());
+    let mut in_comment = false;
+    for c in moves.chars() {
+        match c {
+            '{' => in_comment = true,
+            '}' if in_comment => {
+                in_comment = false;
+                without_comments.push(' ');
+            }
+            _ if !in_comment => without_comments.push(c),
+            _ => {}
+        }
+    }
+
+    without_comments
+        .split_whitespace()
+        .filter(|token| !is_result_token(token) && !token.starts_with('$'))
+        .map(|token| {
+            let number_len = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(token.len());
+            if number_len > 0 && token[number_len..].starts_with('.') {
+                token[number_len..].trim_start_matches('.')
+            } else {
+                token
+            }
+        })
+        .filter(|san| !san.is_empty())
+        .map(|san| san.to_string())
+        .collect()
+}
+
+pub fn opening_move_frequency(games: &[Game]) -> HashMap<String, u32> {
+    let mut frequency = HashMap::new();
+
+    for game in games {
+        if let Some(first_move) = parse_moves(&game.moves).into_iter().next() {
+            *frequency.entry(first_move).or_insert(0) += 1;
+        }
+    }
+
+    frequency
+}
+
+// Most played first, ties by move.
+pub fn export_opening_move_frequency(frequency: &HashMap<String, u32>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut moves: Vec<(&String, &u32)> = frequency.iter().collect();
+    moves.sort_by(|(move_a, a), (move_b, b)| b.cmp(a).then_with(|| move_a.cmp(move_b)));
+    for (first_move, count) in moves {
+        wtr.serialize((first_move, count))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn validate_move_counts(games: &[Game]) -> Vec<(String, u32, usize)> {
+    games
+        .iter()
+        .filter_map(|game| {
+            let claimed_total = game.total_moves?;
+            let actual_count = parse_moves(&game.moves).len();
+            (claimed_total as usize != actual_count).then(|| (game.game_id.clone(), claimed_total, actual_count))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_moves() {
+        assert_eq!(parse_moves("1. e4 e5 2. Nf3"), vec!["e4", "e5", "Nf3"]);
+        assert_eq!(parse_moves("1.d4 d5 2.c4"), vec!["d4", "d5", "c4"]);
+        assert_eq!(
+            parse_moves("1. e4 { [%clk 0:03:00] } 1... c5 { [%clk 0:02:59] } 1-0"),
+            vec!["e4", "c5"]
+        );
+        assert!(parse_moves("").is_empty());
+    }
+
+    #[test]
+    fn test_opening_move_frequency() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                moves: "1. e4 e5 2. Nf3".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                moves: "1. e4 c5".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                moves: "1. d4 d5".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let frequency = opening_move_frequency(&games);
+        assert_eq!(frequency.len(), 2);
+        assert_eq!(frequency["e4"], 2);
+        assert_eq!(frequency["d4"], 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("first_moves.csv");
+        export_opening_move_frequency(&frequency, file_path.to_str().unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "e4,2\nd4,1\n");
+    }
+
+    #[test]
+    fn test_validate_move_counts() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                moves: "1. e4 e5 2. Nf3".to_string(),
+                total_moves: Some(3),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                moves: "1. d4 d5".to_string(),
+                total_moves: Some(10),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                moves: "1. c4".to_string(),
+                total_moves: None,
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(validate_move_counts(&games), vec![("2".to_string(), 10, 2)]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use crate::analysis::Game;
+use crate::error::AnalysisError;
+
+// Reads every game in a PGN file one line at a time, so only the game being
+// parsed is held as text. A game is its tag pairs followed by its movetext;
+// the next tag pair after movetext starts the next game. `.gz` files are
+// decompressed.
+//
+// GameID comes from a GameID tag, else the last part of the Site URL (the
+// Lichess game id), else the game's position in the file. Tags that are
+// missing or "?" leave the field at its default.
+pub fn read_games_from_pgn(path: &str) -> Result<Vec<Game>, AnalysisError> {
+    let reader = crate::input::open_input(Path::new(path))?;
+    let mut games = Vec::new();
+    let mut tags: HashMap<String, String> = HashMap::new();
+    let mut movetext = String::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if !movetext.is_empty() {
+                games.push(build_game(std::mem::take(&mut tags), std::mem::take(&mut movetext), games.len() + 1));
+            }
+            let (name, value) = parse_tag(line)
+                .ok_or_else(|| AnalysisError::Parse(format!("{} line {}: malformed tag pair: {}", path, index + 1, line)))?;
+            tags.insert(name, value);
+        } else {
+            if !movetext.is_empty() {
+                movetext.push(' ');
+            }
+            movetext.push_str(line);
+        }
+    }
+
+    if !tags.is_empty() || !movetext.is_empty() {
+        games.push(build_game(tags, movetext, games.len() + 1));
+    }
+
+    Ok(games)
+}
+
+// `[Name "Value"]`, where the value may escape quotes and backslashes.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let (name, rest) = inner.split_once(char::is_whitespace)?;
+    let quoted = rest.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut value = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            value.push(chars.next()?);
+        } else {
+            value.push(c);
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+fn build_game(mut tags: HashMap<String, String>, movetext: String, number: usize) -> Game {
+    tags.retain(|_, value| !value.is_empty() && value != "?");
+    let mut take = |name: &str| tags.remove(name).unwrap_or_default();
+
+    let site = take("Site");
+    let game_id = match take("GameID") {
+        id if !id.is_empty() => id,
+        _ => match site.rsplit('/').next() {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => number.to_string(),
+        },
+    };
+    let white_elo = take("WhiteElo").parse().ok();
+    let black_elo = take("BlackElo").parse().ok();
+    let white_rating_diff = take("WhiteRatingDiff").parse().ok();
+    let black_rating_diff = take("BlackRatingDiff").parse().ok();
+    let date = Some(take("UTCDate"))
+        .filter(|date| !date.is_empty())
+        .or_else(|| Some(take("Date")).filter(|date| !date.is_empty()));
+
+    Game {
+        game_id,
+        event: take("Event"),
+        white: take("White"),
+        white_elo,
+        white_rating_diff,
+        black: take("Black"),
+        black_elo,
+        black_rating_diff,
+        moves: movetext,
+        eco: take("ECO"),
+        opening: take("Opening"),
+        time_control: take("TimeControl"),
+        result: take("Result"),
+        date,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const TWO_GAMES: &str = r#"[Event "Rated Blitz game"]
+[Site "https://lichess.org/abcd1234"]
+[White "Player1"]
+[Black "Player2"]
+[Result "1-0"]
+[UTCDate "2016.06.30"]
+[WhiteElo "1650"]
+[BlackElo "?"]
+[WhiteRatingDiff "+9"]
+[ECO "C20"]
+[Opening "King's Pawn Game"]
+[TimeControl "300+0"]
+
+1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6
+4. Qxf7# 1-0
+
+[Event "Casual game"]
+[White "Player \"Three\""]
+[Black "Player1"]
+[Result "1/2-1/2"]
+
+1. d4 d5 { a quiet start } 1/2-1/2
+"#;
+
+    #[test]
+    fn test_read_games_from_pgn() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("games.pgn");
+        std::fs::write(&file_path, TWO_GAMES).unwrap();
+
+        let games = read_games_from_pgn(file_path.to_str().unwrap()).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let first = &games[0];
+        assert_eq!(first.game_id, "abcd1234");
+        assert_eq!(first.event, "Rated Blitz game");
+        assert_eq!((first.white.as_str(), first.black.as_str()), ("Player1", "Player2"));
+        assert_eq!(first.result, "1-0");
+        assert_eq!(first.white_elo, Some(1650));
+        assert_eq!(first.black_elo, None);
+        assert_eq!(first.white_rating_diff, Some(9.0));
+        assert_eq!(first.eco, "C20");
+        assert_eq!(first.opening, "King's Pawn Game");
+        assert_eq!(first.time_control, "300+0");
+        assert_eq!(first.date.as_deref(), Some("2016.06.30"));
+        assert_eq!(first.moves, "1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0");
+        assert_eq!(crate::moves::parse_moves(&first.moves).len(), 7);
+
+        let second = &games[1];
+        assert_eq!(second.game_id, "2");
+        assert_eq!(second.white, "Player \"Three\"");
+        assert_eq!(second.result, "1/2-1/2");
+        assert_eq!(second.eco, "");
+        assert_eq!(second.date, None);
+    }
+
+    #[test]
+    fn test_read_games_from_pgn_bad_tag() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("games.pgn");
+        std::fs::write(&file_path, "[White \"Player1\"]\n[Black Player2]\n\n1. e4 *\n").unwrap();
+
+        let err = read_games_from_pgn(file_path.to_str().unwrap()).unwrap_err().to_string();
+        assert!(err.contains("line 2"));
+    }
+}
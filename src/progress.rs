@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Progress goes to stderr so it never mixes with output piped from stdout.
+// Set once from the command line; every counter checks it before printing.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+const REPORT_EVERY: usize = 100_000;
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub struct Progress {
+    label: String,
+    count: usize,
+    every: usize,
+}
+
+impl Progress {
+    pub fn new(label: &str) -> Progress {
+        Progress::with_interval(label, REPORT_EVERY)
+    }
+
+    pub fn with_interval(label: &str, every: usize) -> Progress {
+        Progress { label: label.to_string(), count: 0, every: every.max(1) }
+    }
+
+    // Counts one row and reports every `every` rows. Returns whether this
+    // row was a reporting point, whether or not output is suppressed.
+    pub fn tick(&mut self) -> bool {
+        self.count += 1;
+        let report = self.count.is_multiple_of(self.every);
+        if report && !is_quiet() {
+            eprintln!("{}: {} rows processed", self.label, self.count);
+        }
+        report
+    }
+
+    // Only worth a final line if at least one interval was reported.
+    pub fn finish(&self) {
+        if self.count >= self.every && !is_quiet() {
+            eprintln!("{}: done, {} rows", self.label, self.count);
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_reports_every_interval() {
+        let mut progress = Progress::with_interval("test", 3);
+        let reports: Vec<bool> = (0..7).map(|_| progress.tick()).collect();
+        assert_eq!(reports, vec![false, false, true, false, false, true, false]);
+        assert_eq!(progress.count(), 7);
+    }
+}
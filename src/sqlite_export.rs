@@ -0,0 +1,156 @@
+use std::collections::{BTreeSet, HashMap};
+use petgraph::graph::{DiGraph, NodeIndex};
+use rusqlite::{params, Connection};
+use crate::analysis::PlayerPerformance;
+use crate::error::AnalysisError;
+
+// Each player gets one row in `players`; the metric tables refer to it by id
+// so they can be joined on `player_id`, and record the input file each row
+// came from in `source`. The tables are recreated here, once per run, so the
+// database always matches the latest run.
+pub fn create_tables(db_path: &str) -> Result<(), AnalysisError> {
+    let conn = Connection::open(db_path)?;
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS centrality;
+         DROP TABLE IF EXISTS performance;
+         DROP TABLE IF EXISTS players;
+         CREATE TABLE players (
+             id INTEGER PRIMARY KEY,
+             name TEXT NOT NULL UNIQUE
+         );
+         CREATE TABLE performance (
+             player_id INTEGER NOT NULL REFERENCES players(id),
+             source TEXT NOT NULL,
+             games_played INTEGER NOT NULL,
+             games_won INTEGER NOT NULL,
+             games_lost INTEGER NOT NULL,
+             games_drawn INTEGER NOT NULL,
+             total_rating_change REAL NOT NULL,
+             win_rate REAL NOT NULL
+         );
+         CREATE TABLE centrality (
+             player_id INTEGER NOT NULL REFERENCES players(id),
+             source TEXT NOT NULL,
+             metric TEXT NOT NULL,
+             score REAL NOT NULL
+         );",
+    )?;
+    Ok(())
+}
+
+// Adds one input's rows to the tables `create_tables` made. A player already
+// added for an earlier input keeps their id.
+pub fn export_to_sqlite(
+    db_path: &str,
+    source: &str,
+    performance: &HashMap<String, PlayerPerformance>,
+    pagerank: &HashMap<NodeIndex, f64>,
+    graph: &DiGraph<String, u32>,
+) -> Result<(), AnalysisError> {
+    let mut conn = Connection::open(db_path)?;
+    let tx = conn.transaction()?;
+
+    let names: BTreeSet<&str> = graph
+        .node_weights()
+        .map(String::as_str)
+        .chain(performance.keys().map(String::as_str))
+        .collect();
+    let mut player_ids = HashMap::new();
+    {
+        let mut insert_player = tx.prepare("INSERT OR IGNORE INTO players (name) VALUES (?1)")?;
+        let mut select_player = tx.prepare("SELECT id FROM players WHERE name = ?1")?;
+        for name in names {
+            insert_player.execute(params![name])?;
+            let id: i64 = select_player.query_row(params![name], |row| row.get(0))?;
+            player_ids.insert(name, id);
+        }
+
+        let mut insert_performance = tx.prepare(
+            "INSERT INTO performance (player_id, source, games_played, games_won, games_lost, games_drawn, total_rating_change, win_rate)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for (player, stats) in performance {
+            insert_performance.execute(params![
+                player_ids[player.as_str()],
+                source,
+                stats.games_played,
+                stats.games_won,
+                stats.games_lost,
+                stats.games_drawn,
+                stats.total_rating_change as f64,
+                stats.win_rate,
+            ])?;
+        }
+
+        let mut insert_centrality = tx.prepare("INSERT INTO centrality (player_id, source, metric, score) VALUES (?1, ?2, ?3, ?4)")?;
+        for (node, score) in pagerank {
+            insert_centrality.execute(params![player_ids[graph[*node].as_str()], source, "pagerank", score])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_export_to_sqlite() {
+        let mut graph = DiGraph::new();
+        let player1 = graph.add_node("Player1".to_string());
+        let player2 = graph.add_node("Player2".to_string());
+        graph.add_edge(player1, player2, 1);
+
+        let mut performance = HashMap::new();
+        let mut stats = PlayerPerformance::default();
+        stats.update("1-0", 8.0);
+        performance.insert("Player1".to_string(), stats);
+
+        let pagerank: HashMap<NodeIndex, f64> = [(player1, 0.25), (player2, 0.75)].into_iter().collect();
+
+        // A second input where Player1 meets someone new.
+        let mut second_graph = DiGraph::new();
+        let second_player1 = second_graph.add_node("Player1".to_string());
+        let player3 = second_graph.add_node("Player3".to_string());
+        second_graph.add_edge(player3, second_player1, 1);
+        let second_pagerank: HashMap<NodeIndex, f64> = [(second_player1, 0.4), (player3, 0.6)].into_iter().collect();
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("metrics.db");
+        let db_path = db_path.to_str().unwrap();
+        create_tables(db_path).unwrap();
+        export_to_sqlite(db_path, "first.csv", &performance, &pagerank, &graph).unwrap();
+        export_to_sqlite(db_path, "second.csv", &HashMap::new(), &second_pagerank, &second_graph).unwrap();
+
+        let conn = Connection::open(db_path).unwrap();
+        let (games_won, score): (u32, f64) = conn
+            .query_row(
+                "SELECT performance.games_won, centrality.score
+                 FROM players
+                 JOIN performance ON performance.player_id = players.id
+                 JOIN centrality ON centrality.player_id = players.id AND centrality.source = performance.source
+                 WHERE players.name = ?1 AND centrality.metric = 'pagerank'",
+                params!["Player1"],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(games_won, 1);
+        assert_eq!(score, 0.25);
+
+        // Both inputs are kept, and Player1 is one player across them.
+        let players: u32 = conn.query_row("SELECT COUNT(*) FROM players", params![], |row| row.get(0)).unwrap();
+        assert_eq!(players, 3);
+        let second_score: f64 = conn
+            .query_row(
+                "SELECT centrality.score FROM players JOIN centrality ON centrality.player_id = players.id
+                 WHERE players.name = ?1 AND centrality.source = ?2",
+                params!["Player1", "second.csv"],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(second_score, 0.4);
+    }
+}
@@ -1,5 +1,9 @@
-use std::collections::HashMap;
-use crate::analysis::Game;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use csv::Writer;
+use crate::analysis::{Game, GameResult, NameNormalization, PlayerNames};
+use crate::error::AnalysisError;
 
 pub fn classify_games_by_eco(games: &[Game]) -> HashMap<String, Vec<&Game>> {
     let mut eco_classifications = HashMap::new();
@@ -15,6 +19,361 @@ pub fn classify_games_by_eco(games: &[Game]) -> HashMap<String, Vec<&Game>> {
     eco_classifications
 }
 
+// How many games each player has played with each ECO code, either color,
+// counted in one pass. Games without an ECO code count under "".
+pub fn player_eco_counts(games: &[Game], normalization: NameNormalization) -> HashMap<String, HashMap<String, u32>> {
+    let mut counts: HashMap<String, HashMap<String, u32>> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        for player in [&game.white, &game.black] {
+            *counts.entry(names.display_name(player)).or_default().entry(game.eco.clone()).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+// ECO codes are a letter A-E followed by two digits, so the letter alone gives
+// the five broad opening families and the first two characters give ranges of
+// ten codes such as C60-C69 for the Ruy Lopez. Games without an ECO code are
+// left out of both groupings.
+pub fn classify_games_by_eco_family(games: &[Game]) -> HashMap<char, Vec<&Game>> {
+    let mut family_classifications = HashMap::new();
+
+    for game in games {
+        if let Some(family) = game.eco.trim().chars().next() {
+            family_classifications
+                .entry(family.to_ascii_uppercase())
+                .or_insert_with(Vec::new)
+                .push(game);
+        }
+    }
+
+    family_classifications
+}
+
+pub fn classify_games_by_eco_range(games: &[Game]) -> HashMap<String, Vec<&Game>> {
+    let mut range_classifications = HashMap::new();
+
+    for game in games {
+        let range: String = game.eco.trim().chars().take(2).collect::<String>().to_ascii_uppercase();
+        if range.chars().count() == 2 {
+            range_classifications
+                .entry(range)
+                .or_insert_with(Vec::new)
+                .push(game);
+        }
+    }
+
+    range_classifications
+}
+
+// Games per ECO family (A-E), per range of ten codes and per code, as (level,
+// group, games) rows from the broadest level down. Each level is sorted by
+// group, and games without a code are left out.
+pub fn export_eco_groups(
+    families: &HashMap<char, Vec<&Game>>,
+    ranges: &HashMap<String, Vec<&Game>>,
+    codes: &HashMap<String, Vec<&Game>>,
+    filepath: &str,
+) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    wtr.write_record(["level", "group", "games"])?;
+    let families: BTreeMap<String, usize> = families.iter().map(|(family, games)| (family.to_string(), games.len())).collect();
+    for (family, count) in families {
+        wtr.serialize(("family", family, count))?;
+    }
+    let ranges: BTreeMap<String, usize> = ranges.iter().map(|(range, games)| (format!("{}0-{}9", range, range), games.len())).collect();
+    for (range, count) in ranges {
+        wtr.serialize(("range", range, count))?;
+    }
+    let codes: BTreeMap<&str, usize> = codes
+        .iter()
+        .filter(|(code, _)| !code.trim().is_empty())
+        .map(|(code, games)| (code.as_str(), games.len()))
+        .collect();
+    for (code, count) in codes {
+        wtr.serialize(("code", code, count))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Keyed on (player, ECO) with (wins, games). Draws count as games played but
+// not as wins, and games whose result can't be read are skipped entirely.
+pub fn win_rate_by_opening(games: &[Game], normalization: NameNormalization) -> HashMap<(String, String), (u32, u32)> {
+    let mut records: HashMap<(String, String), (u32, u32)> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        let result = match GameResult::from_game(game) {
+            Some(result) => result,
+            None => continue,
+        };
+
+        for (player, won) in [
+            (&game.white, result == GameResult::WhiteWin),
+            (&game.black, result == GameResult::BlackWin),
+        ] {
+            let record = records.entry((names.display_name(player), game.eco.clone())).or_insert((0, 0));
+            if won {
+                record.0 += 1;
+            }
+            record.1 += 1;
+        }
+    }
+
+    records
+}
+
+pub fn export_win_rate_by_opening(records: &HashMap<(String, String), (u32, u32)>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut keys: Vec<&(String, String)> = records.keys().collect();
+    keys.sort();
+    for key @ (player, eco) in keys {
+        let (wins, games) = records[key];
+        let win_rate = if games > 0 { wins as f64 / games as f64 } else { 0.0 };
+        wtr.serialize((player, eco, games, win_rate))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Shannon entropy, in bits, of the ECO codes each player has appeared in
+// with either color. Always the same opening gives 0, and n openings played
+// equally often give log2(n). Games without an ECO code are ignored.
+pub fn opening_diversity(games: &[Game], normalization: NameNormalization) -> HashMap<String, f64> {
+    let mut eco_counts: HashMap<String, BTreeMap<&str, u32>> = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+    for game in games {
+        if game.eco.is_empty() {
+            continue;
+        }
+        for player in [&game.white, &game.black] {
+            *eco_counts.entry(names.display_name(player)).or_default().entry(&game.eco).or_insert(0) += 1;
+        }
+    }
+
+    eco_counts
+        .into_iter()
+        .map(|(player, counts)| {
+            let total = counts.values().sum::<u32>() as f64;
+            let entropy = counts
+                .values()
+                .map(|&count| {
+                    let share = count as f64 / total;
+                    share * (1.0 / share).log2()
+                })
+                .sum::<f64>();
+            (player, entropy)
+        })
+        .collect()
+}
+
+pub fn export_opening_diversity(diversity: &HashMap<String, f64>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut players: Vec<&String> = diversity.keys().collect();
+    players.sort();
+    for player in players {
+        wtr.serialize((player, diversity[player]))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// (white_wins, black_wins, draws) per ECO code over every game, whoever
+// played it. Games whose result can't be read are skipped.
+pub fn eco_outcome_stats(games: &[Game]) -> HashMap<String, (u32, u32, u32)> {
+    let mut stats: HashMap<String, (u32, u32, u32)> = HashMap::new();
+
+    for game in games {
+        let result = match GameResult::from_game(game) {
+            Some(result) => result,
+            None => continue,
+        };
+
+        let entry = stats.entry(game.eco.clone()).or_insert((0, 0, 0));
+        match result {
+            GameResult::WhiteWin => entry.0 += 1,
+            GameResult::BlackWin => entry.1 += 1,
+            GameResult::Draw => entry.2 += 1,
+        }
+    }
+
+    stats
+}
+
+// white_score_pct counts a draw as half a point for white.
+pub fn export_eco_outcome_stats(stats: &HashMap<String, (u32, u32, u32)>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    wtr.write_record(["eco", "white_wins", "black_wins", "draws", "white_score_pct"])?;
+    let mut ecos: Vec<&String> = stats.keys().collect();
+    ecos.sort();
+    for eco in ecos {
+        let (white_wins, black_wins, draws) = stats[eco];
+        let games = white_wins + black_wins + draws;
+        let white_score_pct = if games > 0 {
+            (white_wins as f64 + draws as f64 / 2.0) / games as f64 * 100.0
+        } else {
+            0.0
+        };
+        wtr.serialize((eco, white_wins, black_wins, draws, white_score_pct))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// How often each opening follows another in the same player's games: for
+// every pair of consecutive games a player has (either color), the
+// (previous ECO, next ECO) count goes up by one, and the counts are summed
+// over players. "Consecutive" means adjacent in `games`, so this assumes the
+// games are in the order they were played. Games without an ECO code are
+// skipped and don't break a player's sequence.
+pub fn opening_transition_matrix(games: &[Game], normalization: NameNormalization) -> HashMap<(String, String), u32> {
+    let mut last_eco: HashMap<String, &str> = HashMap::new();
+    let mut transitions = HashMap::new();
+    let names = PlayerNames::new(games, normalization);
+
+    for game in games {
+        if game.eco.is_empty() {
+            continue;
+        }
+        for player in [&game.white, &game.black] {
+            if let Some(previous) = last_eco.insert(names.display_name(player), &game.eco) {
+                *transitions.entry((previous.to_string(), game.eco.clone())).or_insert(0) += 1;
+            }
+        }
+    }
+
+    transitions
+}
+
+pub fn export_opening_transition_matrix(transitions: &HashMap<(String, String), u32>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    wtr.write_record(["prev_eco", "next_eco", "count"])?;
+    let mut keys: Vec<&(String, String)> = transitions.keys().collect();
+    keys.sort();
+    for key @ (previous, next) in keys {
+        wtr.serialize((previous, next, transitions[key]))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub base_seconds: u32,
+    pub increment_seconds: u32,
+}
+
+impl TimeControl {
+    pub fn parse(s: &str) -> Option<TimeControl> {
+        let s = s.trim();
+        let (base, increment) = match s.split_once('+') {
+            Some((base, increment)) => (base, increment),
+            None => (s, "0"),
+        };
+
+        Some(TimeControl {
+            base_seconds: base.trim().parse().ok()?,
+            increment_seconds: increment.trim().parse().ok()?,
+        })
+    }
+
+    // Lichess thresholds on the estimated game duration, base + 40 * increment.
+    // Done in u64, which can't overflow for any pair of u32 inputs.
+    pub fn category(&self) -> &'static str {
+        let estimated_seconds = u64::from(self.base_seconds) + 40 * u64::from(self.increment_seconds);
+        if estimated_seconds < 180 {
+            "bullet"
+        } else if estimated_seconds < 480 {
+            "blitz"
+        } else if estimated_seconds < 1500 {
+            "rapid"
+        } else {
+            "classical"
+        }
+    }
+}
+
+pub fn classify_games_by_time_control(games: &[Game]) -> HashMap<&'static str, Vec<&Game>> {
+    let mut time_control_classifications = HashMap::new();
+
+    for game in games {
+        if let Some(time_control) = TimeControl::parse(&game.time_control) {
+            time_control_classifications
+                .entry(time_control.category())
+                .or_insert_with(Vec::new)
+                .push(game);
+        }
+    }
+
+    time_control_classifications
+}
+
+// Games with no TotalMoves are left out of the average rather than counted as
+// zero-move games, and a category only appears once it has a counted game.
+pub fn avg_moves_by_time_control(games: &[Game]) -> HashMap<String, f64> {
+    classify_games_by_time_control(games)
+        .into_iter()
+        .filter_map(|(category, games)| {
+            let move_counts: Vec<u32> = games.iter().filter_map(|game| game.total_moves).collect();
+            if move_counts.is_empty() {
+                return None;
+            }
+            let average = move_counts.iter().map(|&moves| moves as f64).sum::<f64>() / move_counts.len() as f64;
+            Some((category.to_string(), average))
+        })
+        .collect()
+}
+
+// Share of each category's games that were drawn, out of the games whose
+// result could be read. Like the averages, a category needs at least one
+// such game to appear.
+pub fn draw_rate_by_time_control(games: &[Game]) -> HashMap<String, f64> {
+    classify_games_by_time_control(games)
+        .into_iter()
+        .filter_map(|(category, games)| {
+            let results: Vec<GameResult> = games.iter().filter_map(|game| GameResult::from_game(game)).collect();
+            if results.is_empty() {
+                return None;
+            }
+            let draws = results.iter().filter(|&&result| result == GameResult::Draw).count();
+            Some((category.to_string(), draws as f64 / results.len() as f64))
+        })
+        .collect()
+}
+
+pub fn export_draw_rate_by_time_control(draw_rates: &HashMap<String, f64>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut categories: Vec<&String> = draw_rates.keys().collect();
+    categories.sort();
+    for category in categories {
+        wtr.serialize((category, draw_rates[category]))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn export_avg_moves_by_time_control(averages: &HashMap<String, f64>, filepath: &str) -> Result<(), AnalysisError> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(filepath)?;
+    let mut wtr = Writer::from_writer(BufWriter::new(file));
+    let mut categories: Vec<&String> = averages.keys().collect();
+    categories.sort();
+    for category in categories {
+        wtr.serialize((category, averages[category]))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +404,295 @@ mod tests {
         assert_eq!(eco_classifications["C60"].len(), 2);
         assert_eq!(eco_classifications["D02"].len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_player_eco_counts() {
+        let games: Vec<Game> = [("Player1", "Player2", "C50"), ("Player2", "Player1", "C50"), ("Player1", "Player3", "B01")]
+            .iter()
+            .map(|&(white, black, eco)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                eco: eco.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let counts = player_eco_counts(&games, NameNormalization::Exact);
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts["Player1"], HashMap::from([("C50".to_string(), 2), ("B01".to_string(), 1)]));
+        assert_eq!(counts["Player2"], HashMap::from([("C50".to_string(), 2)]));
+        assert_eq!(counts["Player3"], HashMap::from([("B01".to_string(), 1)]));
+    }
+
+    #[test]
+    fn test_classify_games_by_eco_family_and_range() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                eco: "C60".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                eco: "C65".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                eco: "C42".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "4".to_string(),
+                eco: "".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let families = classify_games_by_eco_family(&games);
+        assert_eq!(families.len(), 1);
+        assert_eq!(families[&'C'].len(), 3);
+
+        let ranges = classify_games_by_eco_range(&games);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges["C6"].len(), 2);
+        assert_eq!(ranges["C4"].len(), 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("eco_groups.csv");
+        export_eco_groups(&families, &ranges, &classify_games_by_eco(&games), file_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "level,group,games\nfamily,C,3\nrange,C40-C49,1\nrange,C60-C69,2\ncode,C42,1\ncode,C60,1\ncode,C65,1\n"
+        );
+    }
+
+    #[test]
+    fn test_win_rate_by_opening() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                result: "1-0".to_string(),
+                eco: "B01".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                white: "Player2".to_string(),
+                black: "Player1".to_string(),
+                result: "0-1".to_string(),
+                eco: "B01".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                white: "Player1".to_string(),
+                black: "Player3".to_string(),
+                result: "1/2-1/2".to_string(),
+                eco: "B01".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let records = win_rate_by_opening(&games, NameNormalization::Exact);
+
+        assert_eq!(records[&("Player1".to_string(), "B01".to_string())], (2, 3));
+        assert_eq!(records[&("Player2".to_string(), "B01".to_string())], (0, 2));
+        assert_eq!(records[&("Player3".to_string(), "B01".to_string())], (0, 1));
+    }
+
+    #[test]
+    fn test_opening_diversity() {
+        let games: Vec<Game> = [("Player1", "Player2", "B01"), ("Player1", "Player3", "B01"), ("Player2", "Player4", "C60"), ("Player4", "Player1", "")]
+            .iter()
+            .enumerate()
+            .map(|(index, &(white, black, eco))| Game {
+                game_id: (index + 1).to_string(),
+                white: white.to_string(),
+                black: black.to_string(),
+                eco: eco.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let diversity = opening_diversity(&games, NameNormalization::Exact);
+
+        assert_eq!(diversity["Player1"].to_string(), "0");
+        assert!((diversity["Player2"] - 1.0).abs() < 1e-9);
+        assert!(diversity["Player2"] > diversity["Player1"]);
+    }
+
+    #[test]
+    fn test_eco_outcome_stats() {
+        let games: Vec<Game> = [("C50", "1-0"), ("C50", "1/2-1/2"), ("C50", "1-0"), ("B01", "0-1"), ("B01", "*")]
+            .iter()
+            .enumerate()
+            .map(|(index, &(eco, result))| Game {
+                game_id: (index + 1).to_string(),
+                white: "Player1".to_string(),
+                black: "Player2".to_string(),
+                eco: eco.to_string(),
+                result: result.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let stats = eco_outcome_stats(&games);
+
+        assert_eq!(stats["C50"], (2, 0, 1));
+        assert_eq!(stats["B01"], (0, 1, 0));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("eco_outcomes.csv");
+        export_eco_outcome_stats(&stats, file_path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&file_path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], "eco,white_wins,black_wins,draws,white_score_pct");
+        assert_eq!(lines[1], "B01,0,1,0,0.0");
+        assert!(lines[2].starts_with("C50,2,0,1,83.33"));
+    }
+
+    #[test]
+    fn test_opening_transition_matrix() {
+        let games: Vec<Game> = [("Player1", "Player2", "C50"), ("Player3", "Player4", ""), ("Player3", "Player1", "C60")]
+            .iter()
+            .map(|&(white, black, eco)| Game {
+                white: white.to_string(),
+                black: black.to_string(),
+                eco: eco.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let transitions = opening_transition_matrix(&games, NameNormalization::Exact);
+
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[&("C50".to_string(), "C60".to_string())], 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("opening_transitions.csv");
+        export_opening_transition_matrix(&transitions, file_path.to_str().unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "prev_eco,next_eco,count\nC50,C60,1\n");
+    }
+
+    #[test]
+    fn test_parse_time_control() {
+        assert_eq!(TimeControl::parse("300+3"), Some(TimeControl { base_seconds: 300, increment_seconds: 3 }));
+        assert_eq!(TimeControl::parse("600"), Some(TimeControl { base_seconds: 600, increment_seconds: 0 }));
+        assert_eq!(TimeControl::parse("-"), None);
+        assert_eq!(TimeControl::parse("60+x"), None);
+    }
+
+    #[test]
+    fn test_time_control_category() {
+        assert_eq!(TimeControl::parse("60+0").unwrap().category(), "bullet");
+        assert_eq!(TimeControl::parse("180+2").unwrap().category(), "blitz");
+        assert_eq!(TimeControl::parse("600+5").unwrap().category(), "rapid");
+        assert_eq!(TimeControl::parse("1800+30").unwrap().category(), "classical");
+        assert_eq!(TimeControl::parse("0+200000000").unwrap().category(), "classical");
+        assert_eq!(TimeControl::parse("4294967295+4294967295").unwrap().category(), "classical");
+    }
+
+    #[test]
+    fn test_avg_moves_by_time_control() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                time_control: "60+0".to_string(),
+                total_moves: Some(40),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                time_control: "120+0".to_string(),
+                total_moves: Some(61),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                time_control: "60+1".to_string(),
+                total_moves: None,
+                ..Default::default()
+            },
+            Game {
+                game_id: "4".to_string(),
+                time_control: "600+5".to_string(),
+                total_moves: Some(80),
+                ..Default::default()
+            },
+        ];
+
+        let averages = avg_moves_by_time_control(&games);
+
+        assert_eq!(averages.len(), 2);
+        assert_eq!(averages["bullet"], 50.5);
+        assert_eq!(averages["rapid"], 80.0);
+    }
+
+    #[test]
+    fn test_draw_rate_by_time_control() {
+        let games: Vec<Game> = [
+            ("5400+30", "1/2-1/2"),
+            ("5400+30", "1/2-1/2"),
+            ("5400+30", "1/2-1/2"),
+            ("5400+30", "1-0"),
+            ("60+0", "1-0"),
+            ("60+0", "0-1"),
+            ("60+0", "0-1"),
+            ("60+0", "1/2-1/2"),
+            ("60+0", "*"),
+            ("-", "1/2-1/2"),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(index, &(time_control, result))| Game {
+            game_id: (index + 1).to_string(),
+            time_control: time_control.to_string(),
+            result: result.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+        let draw_rates = draw_rate_by_time_control(&games);
+
+        assert_eq!(draw_rates.len(), 2);
+        assert_eq!(draw_rates["classical"], 0.75);
+        assert_eq!(draw_rates["bullet"], 0.25);
+        assert!(draw_rates["classical"] > draw_rates["bullet"]);
+    }
+
+    #[test]
+    fn test_classify_games_by_time_control() {
+        let games = vec![
+            Game {
+                game_id: "1".to_string(),
+                time_control: "60+0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "2".to_string(),
+                time_control: "300+0".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "3".to_string(),
+                time_control: "180+2".to_string(),
+                ..Default::default()
+            },
+            Game {
+                game_id: "4".to_string(),
+                time_control: "-".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let time_control_classifications = classify_games_by_time_control(&games);
+
+        assert_eq!(time_control_classifications.len(), 2);
+        assert_eq!(time_control_classifications["bullet"].len(), 1);
+        assert_eq!(time_control_classifications["blitz"].len(), 2);
+    }
+}